@@ -1,4 +1,7 @@
-use crate::files::manifest::{create_manifest_file, get_manifest};
+use crate::files::{
+    manifest::{get_manifest, write_manifest},
+    project::Project,
+};
 use anyhow::Result;
 use structopt::StructOpt;
 
@@ -8,10 +11,10 @@ pub struct AuthorParams {
 }
 
 impl AuthorParams {
-    pub fn run(&self) -> Result<()> {
-        let mut manifest = get_manifest()?;
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut manifest = get_manifest(project)?;
         manifest.author = self.author.clone();
-        manifest.to_writer(create_manifest_file()?)?;
+        write_manifest(project, &manifest)?;
         Ok(())
     }
 }
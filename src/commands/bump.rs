@@ -1,10 +1,9 @@
 use crate::files::{
-    manifest::{create_manifest_file, get_manifest, Manifest, MANIFEST_FILE},
-    CWD,
+    manifest::{get_manifest, write_manifest, Manifest},
+    project::Project,
 };
 use anyhow::{anyhow, Context, Result};
 use git2::{ObjectType, Repository, RepositoryState};
-use once_cell::sync::Lazy;
 use semver::{Identifier, Version};
 use structopt::StructOpt;
 
@@ -61,8 +60,23 @@ struct BumpFlags {
 }
 
 impl BumpParams {
-    pub fn run(&self) -> Result<()> {
-        let mut manifest = get_manifest()?;
+    /// Whether this bump will commit and tag the new version.
+    pub fn git_tag(&self) -> bool {
+        match self.field.as_ref() {
+            Some(BumpField::Major { flags })
+            | Some(BumpField::Minor { flags })
+            | Some(BumpField::Patch { flags })
+            | Some(BumpField::Pre { flags })
+                if self.version.is_none() =>
+            {
+                flags.git_tag
+            }
+            _ => self.flags.git_tag,
+        }
+    }
+
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut manifest = get_manifest(project)?;
         let mut git = self.flags.git_tag;
         match self.version.as_ref() {
             Some(ver) => manifest.version = ver.clone(),
@@ -94,16 +108,34 @@ impl BumpParams {
                 }
             },
         }
-        manifest.to_writer(create_manifest_file()?)?;
+        write_manifest(project, &manifest)?;
         if git {
-            let repo = Repository::open(Lazy::force(&CWD))?;
+            let repo = Repository::discover(project.root())?;
             // Make sure the repo is in a clean state (not in the middle of rebase or merge)
             if repo.state() != RepositoryState::Clean {
                 return Err(anyhow!("repository is not in a clean state"));
             }
             // Stage the manifest file on top of any currently staged objects.
+            let workdir = repo
+                .workdir()
+                .ok_or_else(|| anyhow!("repository does not have a working directory"))?
+                .canonicalize()
+                .with_context(|| "could not normalize repository working directory")?;
+            let manifest_path = project.manifest_file().canonicalize().with_context(|| {
+                format!(
+                    "could not normalize {}",
+                    project.manifest_file().to_string_lossy()
+                )
+            })?;
+            let manifest_path = manifest_path.strip_prefix(&workdir).with_context(|| {
+                format!(
+                    "{} is not in the repository at {}",
+                    project.manifest_file().to_string_lossy(),
+                    workdir.to_string_lossy()
+                )
+            })?;
             let mut idx = repo.index().expect("git repository should have an index");
-            idx.add_path(&MANIFEST_FILE)
+            idx.add_path(manifest_path)
                 .expect("could not add file we just wrote to disk to add to repository");
             let tree = idx.write_tree().unwrap();
             idx.write().unwrap();
@@ -0,0 +1,103 @@
+use crate::{
+    files::{
+        manifest::get_manifest, mod_loader::ModLoader, mod_metadata::installed_mod_jars,
+        project::Project,
+    },
+    utils::version_range::version_matches,
+};
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use structopt::StructOpt;
+
+// Version of something that is installed but its version is not known.
+const UNKNOWN_VERSION: &str = "*";
+
+#[derive(StructOpt, Debug)]
+pub struct CheckParams {}
+
+impl CheckParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let manifest = get_manifest(project)?;
+        let jars = installed_mod_jars(project, &manifest)?;
+        // Jars without metadata in the manifest were read by installed_mod_jars
+        let mods_dir = project.mods_dir();
+        for m in manifest.effective_mods() {
+            if m.metadata.is_none() && !mods_dir.join(&m.file_name).exists() {
+                println!(
+                    "warning: {} has not been read yet, run sync to check its dependencies",
+                    m.file_name
+                );
+            }
+        }
+        // Mod IDs that are installed and their versions
+        let mut installed: BTreeMap<String, String> = BTreeMap::new();
+        let _ = installed.insert(
+            "minecraft".to_string(),
+            manifest.minecraft_version.to_string(),
+        );
+        let _ = installed.insert("java".to_string(), UNKNOWN_VERSION.to_string());
+        let loader_version = manifest.mod_loader_version.to_string();
+        if let Some(loader) = manifest.mod_loader {
+            let _ = installed.insert(loader.dependency_id().to_string(), loader_version);
+            if loader == ModLoader::Quilt {
+                // Quilt can load Fabric mods but its version is not the Fabric version
+                let _ = installed.insert(
+                    ModLoader::Fabric.dependency_id().to_string(),
+                    UNKNOWN_VERSION.to_string(),
+                );
+            }
+        }
+        for jar in &jars {
+            for m in &jar.mods {
+                let version = m.version.as_deref().unwrap_or(UNKNOWN_VERSION);
+                let _ = installed
+                    .entry(m.id.to_lowercase())
+                    .or_insert_with(|| version.to_string());
+                for (id, version) in m.provides.iter().flatten() {
+                    let _ = installed
+                        .entry(id.to_lowercase())
+                        .or_insert_with(|| version.clone());
+                }
+            }
+        }
+        let mut problems = Vec::new();
+        for jar in &jars {
+            for m in &jar.mods {
+                for (dep, range) in m.dependencies.iter().flatten() {
+                    let version = match installed.get(&dep.to_lowercase()) {
+                        Some(v) => v,
+                        None => {
+                            problems.push(format!(
+                                "{} in {} requires {} {} but it is not installed",
+                                m.id, jar.source, dep, range
+                            ));
+                            continue;
+                        }
+                    };
+                    if version == UNKNOWN_VERSION {
+                        continue;
+                    }
+                    match version_matches(range, version) {
+                        Some(true) => {}
+                        Some(false) => problems.push(format!(
+                            "{} in {} requires {} {} but {} is installed",
+                            m.id, jar.source, dep, range, version
+                        )),
+                        None => println!(
+                            "warning: could not understand version range {} of {} in {}",
+                            range, dep, jar.source
+                        ),
+                    }
+                }
+            }
+        }
+        for p in &problems {
+            println!("{}", p);
+        }
+        if !problems.is_empty() {
+            return Err(anyhow!("found {} dependency problems", problems.len()));
+        }
+        println!("all dependencies are installed");
+        Ok(())
+    }
+}
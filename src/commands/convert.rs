@@ -0,0 +1,55 @@
+use crate::files::{
+    manifest::{read_manifest_file, write_manifest},
+    manifest_format::ManifestFormat,
+    project::Project,
+};
+use anyhow::{anyhow, Context, Result};
+use std::fs::rename;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct ConvertParams {
+    /// Format to convert the manifest to.
+    #[structopt(long = "to", possible_values = &["yaml", "toml", "json"])]
+    to: ManifestFormat,
+}
+
+impl ConvertParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let path = project.manifest_file();
+        let from = ManifestFormat::from_path(path)?;
+        if from == self.to {
+            return Err(anyhow!(
+                "{} is already {}",
+                path.to_string_lossy(),
+                self.to.extension()
+            ));
+        }
+        let target = path.with_extension(self.to.extension());
+        if target.exists() {
+            return Err(anyhow!("{} already exists", target.to_string_lossy()));
+        }
+        let (manifest, _) = read_manifest_file(path)?;
+        write_manifest(
+            &Project::new(Some(project.root()), Some(&target))?,
+            &manifest,
+        )?;
+        // Keep the old manifest around without it being found next to the new one. undo
+        // can not bring it back since it swaps files of the new manifest's format.
+        let backup = project.manifest_backup_file();
+        rename(path, &backup).with_context(|| {
+            format!(
+                "could not move {} to {}",
+                path.to_string_lossy(),
+                backup.to_string_lossy()
+            )
+        })?;
+        println!(
+            "converted {} to {}, the old manifest is kept as {} (undo can not bring it back)",
+            path.to_string_lossy(),
+            target.to_string_lossy(),
+            backup.to_string_lossy()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    files::project::Project,
+    utils::{murmur2::fingerprint, table::print_table},
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha512};
+use std::{fs::read, io::stdout, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct FingerprintParams {
+    /// Files to fingerprint.
+    #[structopt(parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+    /// Print JSON instead of a table.
+    #[structopt(long = "json")]
+    json: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct FileFingerprint {
+    file: String,
+    fingerprint: u32,
+    size: u64,
+    sha1: String,
+    sha512: String,
+}
+
+impl FingerprintParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut fingerprints = Vec::new();
+        for file in &self.files {
+            let path = project.root().join(file);
+            let buf = read(&path)
+                .with_context(|| format!("could not read {}", path.to_string_lossy()))?;
+            fingerprints.push(FileFingerprint {
+                file: file.to_string_lossy().into(),
+                fingerprint: fingerprint(&buf),
+                size: buf.len() as u64,
+                sha1: hex::encode(Sha1::digest(&buf)),
+                sha512: hex::encode(Sha512::digest(&buf)),
+            });
+        }
+        if self.json {
+            serde_json::to_writer_pretty(stdout(), &fingerprints)
+                .with_context(|| "could not serialize fingerprints")?;
+            println!();
+            return Ok(());
+        }
+        let mut rows = vec![vec![
+            "FILE".to_string(),
+            "FINGERPRINT".to_string(),
+            "SIZE".to_string(),
+            "SHA1".to_string(),
+            "SHA512".to_string(),
+        ]];
+        for f in fingerprints {
+            rows.push(vec![
+                f.file,
+                f.fingerprint.to_string(),
+                f.size.to_string(),
+                f.sha1,
+                f.sha512,
+            ]);
+        }
+        print_table(&rows);
+        Ok(())
+    }
+}
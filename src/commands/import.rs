@@ -0,0 +1,182 @@
+use crate::{
+    files::{
+        manifest::{clean_path, write_manifest, Manifest, Mod},
+        manifest_json::{ManifestJson, MANIFEST_JSON_FILE},
+        minecraft_instance::InstalledAddon,
+        project::Project,
+    },
+    utils::twitch_api::TwitchAPI,
+};
+use anyhow::{anyhow, Context, Result};
+use semver::Version;
+use std::{
+    collections::BTreeSet,
+    fs::{create_dir_all, File},
+    io::{copy, BufReader, BufWriter},
+    path::{Component, PathBuf},
+    sync::Arc,
+};
+use structopt::StructOpt;
+use tokio::task;
+use zip::ZipArchive;
+
+#[derive(StructOpt, Debug)]
+pub struct ImportParams {
+    /// CurseForge modpack zip to import.
+    #[structopt(parse(from_os_str))]
+    zip: PathBuf,
+    /// Overwrite files in the pack directory with the overrides from the zip.
+    #[structopt(short = "f", long = "force")]
+    force: bool,
+    /// Version of the pack, for packs whose version is not semver.
+    #[structopt(long = "version")]
+    version: Option<Version>,
+}
+
+impl ImportParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        if project.manifest_file().exists() {
+            return Err(anyhow!(
+                "{} already exists",
+                project.manifest_file().to_string_lossy()
+            ));
+        }
+        let zip_path = project.root().join(&self.zip);
+        let mut archive = ZipArchive::new(BufReader::new(File::open(&zip_path).with_context(
+            || format!("could not open {} for reading", zip_path.to_string_lossy()),
+        )?))
+        .with_context(|| format!("{} is not a valid zip", zip_path.to_string_lossy()))?;
+        let manifest_json = ManifestJson::from_reader(
+            archive
+                .by_name(&MANIFEST_JSON_FILE.to_string_lossy())
+                .with_context(|| {
+                    format!(
+                        "could not find {} in {}",
+                        MANIFEST_JSON_FILE.to_string_lossy(),
+                        zip_path.to_string_lossy()
+                    )
+                })?,
+        )?;
+        let mut manifest = Manifest::default();
+        manifest.name = manifest_json.name.clone();
+        manifest.version = match &self.version {
+            Some(v) => v.clone(),
+            None => manifest_json
+                .pack_version()
+                .map_err(|e| anyhow!("{}, use --version to set one", e))?,
+        };
+        manifest.author = manifest_json.author.clone();
+        manifest.minecraft_version = manifest_json.minecraft.version.clone();
+        if let Some((loader, version)) = manifest_json.minecraft.get_mod_loader() {
+            manifest.mod_loader = Some(loader);
+            manifest.mod_loader_version = version;
+        }
+        // Get all the mod information before touching the pack directory
+        let files = manifest_json
+            .get_files()
+            .map(|files| files.iter().map(|f| (f.project_id, f.file_id)).collect())
+            .unwrap_or_default();
+        for module in fetch_mods(files)? {
+            let _ = manifest.add_mod(module);
+        }
+        for include in
+            extract_overrides(&mut archive, manifest_json.overrides(), project, self.force)?
+        {
+            let _ = manifest.add_include(include);
+        }
+        write_manifest(project, &manifest)
+    }
+}
+
+/// Extracts the overrides into the pack directory and returns their top level paths.
+///
+/// Without `force` nothing is extracted if any of the files already exists.
+fn extract_overrides<R: std::io::Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    overrides: &str,
+    project: &Project,
+    force: bool,
+) -> Result<BTreeSet<String>> {
+    let mut includes = BTreeSet::new();
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        let rel = match file.enclosed_name().map(|n| n.strip_prefix(overrides)) {
+            Some(Ok(rel)) if rel.components().next().is_some() => rel.to_path_buf(),
+            _ => continue,
+        };
+        // Ignore anything that would end up outside of the pack directory
+        if !rel.components().all(|c| matches!(c, Component::Normal(_))) {
+            println!("skipping {} as it is not a safe path", file.name());
+            continue;
+        }
+        if let Some(Component::Normal(top)) = rel.components().next() {
+            let _ = includes.insert(clean_path(top));
+        }
+        let target = project.root().join(&rel);
+        if !file.is_dir() && target.exists() && !force {
+            return Err(anyhow!(
+                "{} already exists, use --force to overwrite it",
+                target.to_string_lossy()
+            ));
+        }
+        entries.push((i, target));
+    }
+    for (i, target) in entries {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() {
+            create_dir_all(&target)
+                .with_context(|| format!("could not create {}", target.to_string_lossy()))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            create_dir_all(parent)
+                .with_context(|| format!("could not create {}", parent.to_string_lossy()))?;
+        }
+        let mut w = BufWriter::new(
+            File::create(&target)
+                .with_context(|| format!("could not create {}", target.to_string_lossy()))?,
+        );
+        let _ = copy(&mut file, &mut w)
+            .with_context(|| format!("could not extract {}", target.to_string_lossy()))?;
+    }
+    Ok(includes)
+}
+
+#[tokio::main]
+async fn fetch_mods(files: Vec<(u32, u32)>) -> Result<Vec<Mod>> {
+    let twitch = Arc::new(TwitchAPI::new());
+    let mut tasks = Vec::new();
+    for (project, file) in files {
+        let twitch = Arc::clone(&twitch);
+        tasks.push(task::spawn(async move {
+            let installed_file = twitch.file(project, file).await?;
+            Ok::<Mod, anyhow::Error>(
+                (&InstalledAddon {
+                    addon_id: project,
+                    installed_file,
+                })
+                    .into(),
+            )
+        }));
+    }
+    let mut mods = Vec::new();
+    let mut was_error = false;
+    for t in tasks {
+        match t.await {
+            Ok(Ok(m)) => mods.push(m),
+            Ok(Err(e)) => {
+                was_error = true;
+                println!("{}", e)
+            }
+            Err(e) => {
+                was_error = true;
+                println!("{}", e)
+            }
+        };
+    }
+    if was_error {
+        return Err(anyhow!("there was an error getting mod information"));
+    }
+    Ok(mods)
+}
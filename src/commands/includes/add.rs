@@ -1,6 +1,9 @@
 use crate::{
-    commands::includes::relative_path,
-    files::manifest::{create_manifest_file, get_manifest},
+    files::{
+        manifest::{clean_path, get_manifest, write_manifest},
+        project::Project,
+    },
+    utils::patterns::{is_pattern, Patterns},
 };
 use anyhow::{anyhow, Result};
 use std::path::PathBuf;
@@ -8,37 +11,49 @@ use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
 pub struct Add {
-    /// Relative path to file to add to includes.
+    /// Add to the paths that are never packed instead.
+    #[structopt(short = "e", long = "exclude")]
+    exclude: bool,
+    /// Relative path to file or glob pattern to add to includes.
     #[structopt(parse(from_os_str))]
     paths: Vec<PathBuf>,
 }
 
 impl Add {
-    pub fn run(&self) -> Result<()> {
-        let mut manifest = get_manifest()?;
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut manifest = get_manifest(project)?;
         for path in self.paths.iter() {
-            if !path.exists() {
-                // TODO Create my own error
-                return Err(anyhow!(format!("{} does not exit", path.to_string_lossy())));
+            let include = path.to_string_lossy().to_string();
+            let include = if is_pattern(&include) {
+                // Make sure the pattern is valid before saving it
+                let _ = Patterns::new(project.root(), [&include])?;
+                include
+            } else {
+                if !project.root().join(path).exists() {
+                    // TODO Create my own error
+                    return Err(anyhow!(format!("{} does not exit", path.to_string_lossy())));
+                }
+                clean_path(project.relative_path(path)?)
+            };
+            if self.exclude {
+                if !manifest.add_exclude_include(include) {
+                    println!("{} is already in the exclude list", path.to_string_lossy());
+                }
+                continue;
             }
-            let cpath = relative_path(path)?;
             // Validate that the path is not already included by another
-            if let Some(p) = manifest.include_contained(&cpath) {
-                println!(
-                    "{} already included by {}",
-                    cpath.to_string_lossy(),
-                    p.to_string_lossy()
-                );
-                return Ok(());
+            if let Some(p) = manifest.include_contained(&include)? {
+                println!("{} already included by {}", include, p);
+                continue;
             }
             // Try and add it
-            if !manifest.add_include(cpath) {
+            if !manifest.add_include(include) {
                 println!("{} is already in the include list", path.to_string_lossy());
-                return Ok(());
+                continue;
             }
             // Cleanup other paths that may be included with this new path
-            manifest.includes_clean();
+            manifest.includes_clean()?;
         }
-        manifest.to_writer(create_manifest_file()?)
+        write_manifest(project, &manifest)
     }
 }
@@ -1,11 +1,10 @@
 mod add;
 mod remove;
 
-use crate::files::CWD;
+use crate::files::project::Project;
 use add::Add;
-use anyhow::{Context, Result};
+use anyhow::Result;
 use remove::Remove;
-use std::path::PathBuf;
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -18,24 +17,10 @@ pub enum Include {
 }
 
 impl Include {
-    pub fn run(&self) -> Result<()> {
+    pub fn run(&self, project: &Project) -> Result<()> {
         match &self {
-            Include::Add(p) => p.run(),
-            Include::Remove(p) => p.run(),
+            Include::Add(p) => p.run(project),
+            Include::Remove(p) => p.run(project),
         }
     }
 }
-
-fn relative_path(p: &PathBuf) -> Result<PathBuf> {
-    let abs = p
-        .canonicalize()
-        .with_context(|| format!("could not normalize {}", p.to_string_lossy()))?;
-    Ok((abs.strip_prefix(CWD.as_path()).with_context(|| {
-        format!(
-            "{} is not in {}",
-            p.to_string_lossy(),
-            CWD.to_string_lossy()
-        )
-    })?)
-    .to_path_buf())
-}
@@ -1,6 +1,9 @@
 use crate::{
-    commands::includes::relative_path,
-    files::manifest::{create_manifest_file, get_manifest},
+    files::{
+        manifest::{clean_path, get_manifest, write_manifest},
+        project::Project,
+    },
+    utils::patterns::is_pattern,
 };
 use anyhow::Result;
 use std::path::PathBuf;
@@ -8,19 +11,36 @@ use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
 pub struct Remove {
-    /// Relative path to file to add to includes.
+    /// Remove from the paths that are never packed instead.
+    #[structopt(short = "e", long = "exclude")]
+    exclude: bool,
+    /// Relative path to file or glob pattern to remove from includes.
     #[structopt(parse(from_os_str))]
     paths: Vec<PathBuf>,
 }
 
 impl Remove {
-    pub fn run(&self) -> Result<()> {
-        let mut manifest = get_manifest()?;
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut manifest = get_manifest(project)?;
         for path in self.paths.iter() {
-            if !manifest.remove_include(&relative_path(path)?) {
+            let include = path.to_string_lossy().to_string();
+            // The path may already be gone so fallback to what was given
+            let include = if is_pattern(&include) {
+                include
+            } else {
+                project
+                    .relative_path(path)
+                    .map(clean_path)
+                    .unwrap_or_else(|_| clean_path(path))
+            };
+            if self.exclude {
+                if !manifest.remove_exclude_include(&include) {
+                    println!("{} was not in the excludes", path.to_string_lossy());
+                }
+            } else if !manifest.remove_include(&include) {
                 println!("{} was not in the includes", path.to_string_lossy());
             }
         }
-        manifest.to_writer(create_manifest_file()?)
+        write_manifest(project, &manifest)
     }
 }
@@ -1,10 +1,16 @@
 use crate::files::{
-    manifest::{create_manifest_file, Manifest, MANIFEST_FILE},
-    minecraft_instance::{get_minecraft_instance, MINECRAFT_INSTANCE_FILE},
+    manifest::{get_instance_manifest, write_manifest, Manifest},
+    mod_loader::ModLoader,
+    project::Project,
+    versions::{LoaderVersion, MinecraftVersion},
 };
 use anyhow::{anyhow, Result};
 use semver::Version;
-use std::io::{stdin, stdout, Write};
+use std::{
+    fmt::Display,
+    io::{stdin, stdout, Write},
+    str::FromStr,
+};
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -20,19 +26,18 @@ pub struct InitParams {
     version: Option<Version>,
     /// Minecraft version to use for the mod pack.
     #[structopt(short = "m", long = "mc_version")]
-    mc_verison: Option<Version>,
+    mc_verison: Option<MinecraftVersion>,
     /// Which mod loader to use.
-    #[structopt(short = "l", long = "loader", default_value = "forge")]
-    loader: String,
+    #[structopt(short = "l", long = "loader", possible_values = &["forge", "neoforge", "fabric", "quilt"])]
+    loader: Option<ModLoader>,
     /// Which version of the mod loader to use.
     #[structopt(long = "loader_version")]
-    loader_version: Option<Version>,
+    loader_version: Option<LoaderVersion>,
 }
 
 impl InitParams {
     fn prompt_for_manifest(&self) -> Result<Manifest> {
         let mut man = Manifest::default();
-        man.mod_loader = self.loader.clone();
         match &self.name {
             Some(s) => man.name = s.clone(),
             None => man.name = prompt_for_string("Pack Name"),
@@ -49,6 +54,10 @@ impl InitParams {
             Some(s) => man.minecraft_version = s.clone(),
             None => man.minecraft_version = prompt_for_version("Minecraft Version", 3)?,
         };
+        match self.loader {
+            Some(l) => man.mod_loader = Some(l),
+            None => man.mod_loader = Some(prompt_for_mod_loader("Mod Loader", 3)?),
+        };
         match &self.loader_version {
             Some(s) => man.mod_loader_version = s.clone(),
             None => man.mod_loader_version = prompt_for_version("Mod Loader Version", 3)?,
@@ -58,24 +67,23 @@ impl InitParams {
 }
 
 impl InitParams {
-    pub fn run(&self) -> Result<()> {
-        if MANIFEST_FILE.exists() {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        if project.manifest_file().exists() {
             return Err(anyhow!(
                 "{} already exists",
-                MANIFEST_FILE.to_string_lossy()
+                project.manifest_file().to_string_lossy()
             ));
         }
-        let manifest = if MINECRAFT_INSTANCE_FILE.exists() {
-            (&get_minecraft_instance()?).into()
-        } else {
-            self.prompt_for_manifest()?
+        let manifest = match project.launcher() {
+            Some(launcher) => get_instance_manifest(project, launcher)?,
+            None => self.prompt_for_manifest()?,
         };
-        manifest.to_writer(create_manifest_file()?)?;
+        write_manifest(project, &manifest)?;
         Ok(())
     }
 }
 
-fn prompt_for_string(prompt: &str) -> String {
+pub fn prompt_for_string(prompt: &str) -> String {
     let mut s = String::new();
     write!(stdout(), "{}: ", prompt).expect("could not write to prompt");
     stdout().flush().expect("could not flush to prompt");
@@ -89,14 +97,36 @@ fn prompt_for_string(prompt: &str) -> String {
     s
 }
 
-fn prompt_for_version(prompt: &str, retries: u8) -> Result<Version> {
+fn prompt_for_mod_loader(prompt: &str, retries: u8) -> Result<ModLoader> {
+    let mut last_err = None;
+    for cur in 0..retries {
+        if cur != 0 {
+            eprintln!(" please retry");
+        }
+        match prompt_for_string(prompt).parse() {
+            Ok(loader) => return Ok(loader),
+            Err(e) => {
+                eprint!("{}", e);
+                last_err = Some(e);
+            }
+        };
+    }
+    eprintln!(" too many attempts");
+    Err(last_err.unwrap_or_else(|| anyhow!("no mod loader given")))
+}
+
+fn prompt_for_version<V>(prompt: &str, retries: u8) -> Result<V>
+where
+    V: FromStr,
+    V::Err: Display,
+{
     let mut cur: u8 = 0;
     let mut last_err = None;
     while cur < retries {
         if cur != 0 {
             eprintln!(" please retry");
         }
-        match Version::parse(&prompt_for_string(prompt)) {
+        match prompt_for_string(prompt).parse() {
             Ok(ver) => return Ok(ver),
             Err(e) => {
                 eprint!("not a valid version");
@@ -108,8 +138,8 @@ fn prompt_for_version(prompt: &str, retries: u8) -> Result<Version> {
     match last_err {
         Some(e) => {
             eprintln!(" too many attempts");
-            Err(anyhow::Error::new(e).context("not a valid version"))
+            Err(anyhow!("not a valid version: {}", e))
         }
-        None => Ok(Version::new(0, 0, 0)),
+        None => Err(anyhow!("no version given")),
     }
 }
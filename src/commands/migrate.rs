@@ -0,0 +1,44 @@
+use crate::files::{
+    manifest::{read_manifest_file, write_manifest, SCHEMA_VERSION},
+    project::Project,
+};
+use anyhow::{anyhow, Result};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct MigrateParams {
+    /// Only check if the manifest needs to be migrated, failing if it does.
+    #[structopt(long = "check")]
+    check: bool,
+}
+
+impl MigrateParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let path = project.manifest_file();
+        let (manifest, from) = read_manifest_file(path)?;
+        if from == SCHEMA_VERSION {
+            println!(
+                "{} is already at schema version {}",
+                path.to_string_lossy(),
+                SCHEMA_VERSION
+            );
+            return Ok(());
+        }
+        if self.check {
+            return Err(anyhow!(
+                "{} is at schema version {} and needs to be migrated to {}",
+                path.to_string_lossy(),
+                from,
+                SCHEMA_VERSION
+            ));
+        }
+        write_manifest(project, &manifest)?;
+        println!(
+            "migrated {} from schema version {} to {}",
+            path.to_string_lossy(),
+            from,
+            SCHEMA_VERSION
+        );
+        Ok(())
+    }
+}
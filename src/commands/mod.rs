@@ -1,6 +1,17 @@
 pub mod author;
 pub mod bump;
+pub mod check;
+pub mod convert;
+pub mod fingerprint;
+pub mod import;
 pub mod includes;
 pub mod init;
+pub mod migrate;
 pub mod pack;
+pub mod scan;
+pub mod schema;
 pub mod sync;
+pub mod undo;
+pub mod update;
+pub mod validate;
+pub mod verify;
@@ -0,0 +1,140 @@
+use crate::files::{
+    manifest::Manifest,
+    mod_metadata::{duplicate_mod_ids, read_jar_metadata, ModJar},
+};
+use anyhow::{Context, Result};
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fmt,
+    fs::metadata,
+    path::{Path, PathBuf},
+};
+
+// Files this big are usually worlds or caches that were included by mistake.
+const HUGE_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+/// A problem found with the pack before it is written.
+#[derive(Debug)]
+pub struct Lint {
+    pub level: Level,
+    pub message: String,
+}
+
+impl Lint {
+    fn warning(message: String) -> Self {
+        Lint {
+            level: Level::Warning,
+            message,
+        }
+    }
+
+    fn error(message: String) -> Self {
+        Lint {
+            level: Level::Error,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.level {
+            Level::Warning => write!(f, "warning: {}", self.message),
+            Level::Error => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+/// Checks the manifest and the files that are about to be packed.
+///
+/// `entries` maps paths in the overrides to the file they come from, and
+/// `unmatched` are the includes that did not pack anything.
+pub fn lint(
+    manifest: &Manifest,
+    entries: &BTreeMap<PathBuf, Option<PathBuf>>,
+    unmatched: &[String],
+) -> Result<Vec<Lint>> {
+    let mut lints = Vec::new();
+    if manifest.name.trim().is_empty() {
+        lints.push(Lint::error("name is empty".to_string()));
+    }
+    if manifest.author.trim().is_empty() {
+        lints.push(Lint::warning("author is empty".to_string()));
+    }
+    if manifest.minecraft_version.is_unknown() {
+        lints.push(Lint::error(
+            "minecraftVersion is missing or unknown".to_string(),
+        ));
+    }
+    if manifest.mod_loader.is_none() {
+        lints.push(Lint::error("modLoader is not set".to_string()));
+    }
+    if manifest.mod_loader_version.is_unknown() {
+        lints.push(Lint::error(
+            "modLoaderVersion is missing or unknown".to_string(),
+        ));
+    }
+    for include in unmatched {
+        lints.push(Lint::warning(format!(
+            "include {} does not match any files",
+            include
+        )));
+    }
+    let mods = manifest.effective_mods();
+    let mut jars: Vec<ModJar> = mods
+        .iter()
+        .filter_map(|m| {
+            let meta = m.metadata.clone()?;
+            Some(ModJar::tracked(
+                Path::new("mods").join(&m.file_name),
+                m.project_id,
+                meta,
+            ))
+        })
+        .collect();
+    for (over, source) in entries {
+        let source = match source {
+            Some(s) => s,
+            None => continue,
+        };
+        if over.parent() == Some(Path::new("mods")) {
+            let file_name = over.file_name().map(|f| f.to_string_lossy());
+            if let Some(m) = mods
+                .iter()
+                .find(|m| Some(m.file_name.as_str()) == file_name.as_deref())
+            {
+                lints.push(Lint::error(format!(
+                    "{} is included and also tracked as project {}, it would be loaded twice",
+                    source.to_string_lossy(),
+                    m.project_id
+                )));
+            } else if over.extension() == Some(OsStr::new("jar")) {
+                match read_jar_metadata(source) {
+                    Ok(meta) => jars.push(ModJar::included(over, meta)),
+                    Err(e) => lints.push(Lint::warning(format!("{:#}", e))),
+                }
+            }
+        }
+        let size = metadata(source)
+            .with_context(|| format!("could not read metadata of {}", source.to_string_lossy()))?
+            .len();
+        if size > HUGE_FILE_SIZE {
+            lints.push(Lint::warning(format!(
+                "{} is {} MiB, make sure it should be packed",
+                source.to_string_lossy(),
+                size / 1024 / 1024
+            )));
+        }
+    }
+    for duplicate in duplicate_mod_ids(&jars) {
+        lints.push(Lint::error(duplicate));
+    }
+    Ok(lints)
+}
@@ -0,0 +1,428 @@
+mod lint;
+
+use crate::{
+    files::{
+        manifest::{clean_path, get_manifest, Manifest},
+        manifest_json::{ManifestJson, MANIFEST_JSON_FILE, MANIFEST_OVERRIDES_FOLDER},
+        mmc_instance::{
+            write_instance_cfg, MmcPack, INSTANCE_CFG_FILE, MMC_MINECRAFT_DIR, MMC_PACK_FILE,
+        },
+        mod_metadata::ModSide,
+        project::{Launcher, Project},
+    },
+    utils::{pack_ignore::PackIgnore, patterns::Patterns},
+};
+use anyhow::{anyhow, Context, Result};
+use git2::Repository;
+use lint::{lint, Level};
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    fs::{create_dir_all, read_dir, File},
+    io::{copy, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use structopt::StructOpt;
+use zip::{write::FileOptions, CompressionMethod, DateTime, ZipWriter};
+
+#[derive(StructOpt, Debug)]
+pub struct PackParams {
+    /// Build the variant of the pack described by this profile.
+    #[structopt(short = "p", long = "profile")]
+    profile: Option<String>,
+    /// Leave out the mods that are only needed on the other side.
+    ///
+    /// Uses the side set on the mod in the manifest, or else the one declared by its jar.
+    #[structopt(long = "side", possible_values = &["client", "server"])]
+    side: Option<ModSide>,
+    /// Also leave out files ignored by the git repository the pack is in.
+    ///
+    /// Files ignored by .mcpackerignore are always left out.
+    #[structopt(short = "g", long = "gitignore")]
+    gitignore: bool,
+    /// Launcher the zip is made for.
+    ///
+    /// A prism zip is a MultiMC instance with the mod jars in it, so mods need to be synced first.
+    #[structopt(long = "format", default_value = "curseforge", possible_values = &["curseforge", "prism", "multimc"])]
+    format: Launcher,
+    /// Print what would be packed instead of creating the zip.
+    #[structopt(short = "l", long = "list")]
+    list: bool,
+    /// Directory to create the zip in [default: the pack directory].
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<PathBuf>,
+    /// Name of the zip file.
+    ///
+    /// Can use {name}, {version}, {mcVersion}, {modLoader} and {modLoaderVersion}.
+    #[structopt(short = "t", long = "template", default_value = "{name}.zip")]
+    template: String,
+    /// How to compress files in the zip.
+    #[structopt(short = "c", long = "compression", default_value = "deflated", possible_values = &["deflated", "stored"])]
+    compression: Compression,
+    /// Deflate compression level from 0 (fastest) to 9 (smallest).
+    #[structopt(long = "level")]
+    level: Option<i32>,
+    /// Overwrite the zip if it already exists.
+    #[structopt(short = "f", long = "force")]
+    force: bool,
+    /// Fail on lint warnings as well as errors.
+    #[structopt(short = "s", long = "strict")]
+    strict: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Compression {
+    Deflated,
+    Stored,
+}
+
+impl FromStr for Compression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "deflated" => Ok(Compression::Deflated),
+            "stored" => Ok(Compression::Stored),
+            _ => Err(anyhow!("{} is not a supported compression", s)),
+        }
+    }
+}
+
+impl PackParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut manifest = get_manifest(project)?;
+        if let Some(profile) = &self.profile {
+            manifest = manifest.with_profile(profile)?;
+        }
+        if let Some(side) = self.side {
+            manifest = manifest.for_side(side);
+        }
+        // Files from this manifest take precedence over the ones it extends.
+        let mut zi = ZipInclude::new();
+        for layer in manifest.layers() {
+            zi.add_layer(layer, self.gitignore)?;
+        }
+        let lints = lint(&manifest, &zi.entries, &zi.unmatched)?;
+        for l in &lints {
+            eprintln!("{}", l);
+        }
+        let (overrides, metadata) = match self.format {
+            Launcher::CurseForge => {
+                let manifest_json: ManifestJson = (&manifest).into();
+                let mut buf = Vec::new();
+                manifest_json.to_writer(&mut buf)?;
+                (
+                    MANIFEST_OVERRIDES_FOLDER.as_path(),
+                    vec![(MANIFEST_JSON_FILE.to_string_lossy().into_owned(), buf)],
+                )
+            }
+            Launcher::Prism => {
+                // MultiMC can not download mods itself so the jars go in the zip
+                zi.add_mod_jars(&manifest, project)?;
+                let mut cfg = Vec::new();
+                write_instance_cfg(&mut cfg, &manifest)?;
+                let mut pack = Vec::new();
+                MmcPack::from_manifest(&manifest)?.to_writer(&mut pack)?;
+                (
+                    Path::new(MMC_MINECRAFT_DIR),
+                    vec![
+                        (INSTANCE_CFG_FILE.to_string(), cfg),
+                        (MMC_PACK_FILE.to_string(), pack),
+                    ],
+                )
+            }
+        };
+        if self.list {
+            for (name, _) in &metadata {
+                println!("{}", name);
+            }
+            for (over, source) in &zi.entries {
+                let over = overrides.join(over);
+                match source {
+                    Some(s) => println!("{} <- {}", over.to_string_lossy(), s.to_string_lossy()),
+                    None => println!("{}/", over.to_string_lossy()),
+                }
+            }
+            return Ok(());
+        }
+        let errors = lints
+            .iter()
+            .filter(|l| self.strict || l.level == Level::Error)
+            .count();
+        if errors > 0 {
+            return Err(anyhow!("pack failed {} lint checks", errors));
+        }
+        // Everything that could change between builds of the same commit is fixed,
+        // entries are sorted and get the same time and permissions,
+        // so the zip can be checksummed.
+        let mod_time = source_date(project);
+        let file_options = match (self.compression, self.level) {
+            (Compression::Stored, Some(_)) => {
+                return Err(anyhow!(
+                    "--level can only be used with deflated compression"
+                ))
+            }
+            (_, Some(level)) if !(0..=9).contains(&level) => {
+                return Err(anyhow!("--level must be between 0 and 9"))
+            }
+            (Compression::Stored, None) => {
+                FileOptions::default().compression_method(CompressionMethod::Stored)
+            }
+            (Compression::Deflated, level) => FileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .compression_level(level),
+        };
+        let file_options = file_options
+            .last_modified_time(mod_time)
+            .unix_permissions(0o644);
+        let dir_options = FileOptions::default()
+            .last_modified_time(mod_time)
+            .unix_permissions(0o755);
+        let zip_name = self
+            .template
+            .replace("{name}", &manifest.name)
+            .replace("{version}", &manifest.version.to_string())
+            .replace("{mcVersion}", &manifest.minecraft_version.to_string())
+            .replace("{modLoader}", manifest.mod_loader.map_or("", |l| l.name()))
+            .replace(
+                "{modLoaderVersion}",
+                &manifest.mod_loader_version.to_string(),
+            );
+        let zip_path = project
+            .root()
+            .join(self.output.as_deref().unwrap_or_else(|| Path::new("")))
+            .join(zip_name);
+        if zip_path.exists() && !self.force {
+            return Err(anyhow!(
+                "{} already exists, use --force to overwrite it",
+                zip_path.to_string_lossy()
+            ));
+        }
+        if let Some(parent) = zip_path.parent() {
+            create_dir_all(parent)
+                .with_context(|| format!("could not create {}", parent.to_string_lossy()))?;
+        }
+        let mut zip_file = ZipWriter::new(BufWriter::new(File::create(&zip_path).with_context(
+            || format!("could not crate {} for writing", zip_path.to_string_lossy()),
+        )?));
+        zip_file.set_comment("Minecraft ModPack made by MCPacker");
+        for (name, contents) in &metadata {
+            zip_file
+                .start_file(name, file_options)
+                .expect("could not start file write");
+            zip_file
+                .write_all(contents)
+                .with_context(|| format!("could not write {} to zip", name))?;
+        }
+        for (over, source) in &zi.entries {
+            let over = overrides.join(over);
+            match source {
+                None => zip_file.add_directory(clean_path(over), dir_options)?,
+                Some(source) => {
+                    zip_file
+                        .start_file(clean_path(over), file_options)
+                        .expect("could not start file write");
+                    let _ = copy(
+                        &mut BufReader::new(File::open(source).with_context(|| {
+                            format!("unable to read file {}", source.to_string_lossy())
+                        })?),
+                        zip_file.by_ref(),
+                    )
+                    .with_context(|| {
+                        format!(
+                            "could not write contents from {} to zip",
+                            source.to_string_lossy()
+                        )
+                    })?;
+                }
+            }
+        }
+        let _ = zip_file.finish().expect("could not finish the zip file");
+        Ok(())
+    }
+}
+
+/// Time to give every entry in the zip.
+///
+/// Uses SOURCE_DATE_EPOCH if it is set, otherwise the time of the commit the
+/// pack is at, and falls back to the earliest time a zip can hold.
+fn source_date(project: &Project) -> DateTime {
+    let epoch = match env::var("SOURCE_DATE_EPOCH") {
+        Ok(s) => s.trim().parse::<i64>().ok(),
+        Err(_) => Repository::discover(project.root())
+            .and_then(|repo| Ok(repo.head()?.peel_to_commit()?.time().seconds()))
+            .ok(),
+    };
+    epoch
+        .and_then(|e| epoch_to_date_time(e).ok())
+        .unwrap_or_default()
+}
+
+// Converts seconds since the unix epoch to a UTC date and time
+// using the days to civil algorithm from http://howardhinnant.github.io/date_algorithms.html
+fn epoch_to_date_time(epoch: i64) -> Result<DateTime, ()> {
+    let days = epoch.div_euclid(86400);
+    let secs = epoch.rem_euclid(86400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    if !(1980..=2107).contains(&year) {
+        return Err(());
+    }
+    DateTime::from_date_and_time(
+        year as u16,
+        month as u8,
+        day as u8,
+        (secs / 3600) as u8,
+        (secs % 3600 / 60) as u8,
+        (secs % 60) as u8,
+    )
+}
+
+struct ZipInclude {
+    // Path in the overrides to the file it comes from, or None for directories.
+    entries: BTreeMap<PathBuf, Option<PathBuf>>,
+    // Exclude patterns of the manifests added so far.
+    excludes: Vec<Patterns>,
+    // Include patterns of the current manifest that packed something.
+    matched: HashSet<String>,
+    // Include patterns that did not pack anything.
+    unmatched: Vec<String>,
+}
+
+impl ZipInclude {
+    fn new() -> Self {
+        ZipInclude {
+            entries: BTreeMap::new(),
+            excludes: Vec::new(),
+            matched: HashSet::new(),
+            unmatched: Vec::new(),
+        }
+    }
+
+    fn add_layer(&mut self, layer: &Manifest, gitignore: bool) -> Result<()> {
+        self.excludes.push(layer.exclude_patterns()?);
+        let includes = layer.include_patterns()?;
+        let ignore = PackIgnore::new(layer.dir(), gitignore)?;
+        for prefix in includes.prefixes() {
+            self.collect(layer.dir(), &includes, &ignore, prefix, false)?;
+        }
+        for pattern in includes.originals() {
+            if !self.matched.contains(pattern) {
+                self.unmatched.push(pattern.to_string());
+            }
+        }
+        self.matched.clear();
+        Ok(())
+    }
+
+    fn add_mod_jars(&mut self, manifest: &Manifest, project: &Project) -> Result<()> {
+        for module in manifest.effective_mods() {
+            let jar = project.mods_dir().join(&module.file_name);
+            if !jar.is_file() {
+                return Err(anyhow!(
+                    "{} is missing, run sync before packing",
+                    jar.to_string_lossy()
+                ));
+            }
+            let _ = self
+                .entries
+                .entry(Path::new("mods").join(&module.file_name))
+                .or_insert(Some(jar));
+        }
+        Ok(())
+    }
+
+    fn excluded(&self, p: &Path, is_dir: bool) -> bool {
+        self.excludes.iter().any(|e| e.matched(p, is_dir).is_some())
+    }
+
+    // Excluded files still count as matched, an include that a more derived
+    // manifest excludes is not wrong.
+    fn collect(
+        &mut self,
+        root: &Path,
+        includes: &Patterns,
+        ignore: &PackIgnore,
+        p: &Path,
+        excluded: bool,
+    ) -> Result<()> {
+        let abs = root.join(p);
+        let top = p.as_os_str().is_empty();
+        if abs.is_dir() {
+            if !top && ignore.is_ignored(&abs, p, true)? {
+                return Ok(());
+            }
+            let excluded = excluded || (!top && self.excluded(p, true));
+            // Directories can be shared between extended manifests so merge their contents.
+            if let Some(pattern) = includes.matched(p, true).filter(|_| !top) {
+                let _ = self.matched.insert(pattern.to_string());
+                if !excluded {
+                    let _ = self.entries.entry(p.to_path_buf()).or_insert(None);
+                }
+            }
+            for entry in read_dir(&abs)
+                .with_context(|| format!("unable to read directory {}", p.to_string_lossy()))?
+            {
+                let path = p.join(entry?.file_name());
+                self.collect(root, includes, ignore, &path, excluded)?;
+            }
+        } else if abs.is_file() {
+            let pattern = match includes.matched(p, false) {
+                Some(pattern) => pattern,
+                None => return Ok(()),
+            };
+            if ignore.is_ignored(&abs, p, false)? {
+                return Ok(());
+            }
+            let _ = self.matched.insert(pattern.to_string());
+            if !excluded && !self.excluded(p, false) {
+                let _ = self.entries.entry(p.to_path_buf()).or_insert(Some(abs));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_dir::TestDir;
+
+    const HEADER: &str =
+        "name: t\nversion: 1.0.0\nauthor: t\nminecraftVersion: 1.20.1\nmodLoaderVersion: 47.2.0\n";
+
+    #[test]
+    fn include_excluded_by_derived_manifest_is_matched() {
+        let dir = TestDir::new("pack-derived");
+        let _ = dir.write(
+            "base/.manifest.yaml",
+            &format!("{}includes:\n  - options.txt\n  - config\n", HEADER),
+        );
+        let _ = dir.write("base/options.txt", "");
+        let _ = dir.write("base/config/a.toml", "");
+        let _ = dir.write(
+            "derived/.manifest.yaml",
+            &format!(
+                "{}extends: ../base/.manifest.yaml\nexcludeIncludes:\n  - options.txt\n",
+                HEADER
+            ),
+        );
+        let project = Project::new(Some(&dir.path().join("derived")), None).unwrap();
+        let manifest = get_manifest(&project).unwrap();
+        let mut zi = ZipInclude::new();
+        for layer in manifest.layers() {
+            zi.add_layer(layer, false).unwrap();
+        }
+        assert_eq!(zi.unmatched, Vec::<String>::new());
+        assert!(!zi.entries.contains_key(Path::new("options.txt")));
+        assert!(zi.entries.contains_key(Path::new("config/a.toml")));
+    }
+}
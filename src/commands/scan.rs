@@ -0,0 +1,118 @@
+use crate::{
+    commands::{init::prompt_for_string, sync::jar_name},
+    files::{
+        manifest::{clean_path, get_manifest, write_manifest, Mod},
+        minecraft_instance::InstalledAddon,
+        project::Project,
+    },
+    utils::{
+        murmur2::fingerprint,
+        twitch_api::{FingerprintMatch, TwitchAPI},
+    },
+};
+use anyhow::{Context, Result};
+use std::{
+    collections::BTreeMap,
+    fs::{read, read_dir},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct ScanParams {
+    /// Add every identified jar without asking.
+    #[structopt(short = "y", long = "yes")]
+    yes: bool,
+}
+
+impl ScanParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut manifest = get_manifest(project)?;
+        let mods_dir = project.mods_dir();
+        if !mods_dir.is_dir() {
+            println!("{} does not exist", mods_dir.to_string_lossy());
+            return Ok(());
+        }
+        let tracked = manifest.mods_by_filename();
+        // Jars in the mods folder the manifest does not know about, by fingerprint
+        let mut unknown: BTreeMap<u32, PathBuf> = BTreeMap::new();
+        for entry in read_dir(&mods_dir)
+            .with_context(|| format!("could not read directory {}", mods_dir.to_string_lossy()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                continue;
+            }
+            let jar = match jar_name(&path) {
+                Some((jar, _)) => jar,
+                None => continue,
+            };
+            let file_name = jar.file_name().unwrap_or_default().to_string_lossy();
+            if tracked.contains_key(file_name.as_ref()) {
+                continue;
+            }
+            let buf = read(&path)
+                .with_context(|| format!("could not read {}", path.to_string_lossy()))?;
+            let _ = unknown.insert(fingerprint(&buf), path);
+        }
+        if unknown.is_empty() {
+            println!("all jars are tracked in the manifest");
+            return Ok(());
+        }
+        let fingerprints: Vec<u32> = unknown.keys().copied().collect();
+        let mut added = 0;
+        for m in find_matches(&fingerprints)? {
+            let path = match unknown.remove(&m.file.package_fingerprint) {
+                Some(p) => p,
+                None => continue,
+            };
+            let question = format!(
+                "{} is {} of project {}, add it to the manifest? [y/N]",
+                path.to_string_lossy(),
+                m.file.file_name,
+                m.id
+            );
+            if !self.yes
+                && !prompt_for_string(&question)
+                    .trim()
+                    .eq_ignore_ascii_case("y")
+            {
+                continue;
+            }
+            let module: Mod = (&InstalledAddon {
+                addon_id: m.id,
+                installed_file: m.file,
+            })
+                .into();
+            println!(
+                "adding {} of project {}",
+                module.file_name, module.project_id
+            );
+            // It would be loaded twice if it was still packed as an override
+            if let Ok(rel) = path.strip_prefix(project.root()) {
+                let _ = manifest.remove_include(&clean_path(rel));
+                if manifest.is_included(rel, false)? {
+                    println!(
+                        "excluding {} from includes so it is not packed twice",
+                        clean_path(rel)
+                    );
+                    let _ = manifest.add_exclude_include(clean_path(rel));
+                }
+            }
+            let _ = manifest.add_mod(module);
+            added += 1;
+        }
+        for path in unknown.values() {
+            println!("{} did not match any file", path.to_string_lossy());
+        }
+        if added > 0 {
+            write_manifest(project, &manifest)?;
+        }
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn find_matches(fingerprints: &[u32]) -> Result<Vec<FingerprintMatch>> {
+    TwitchAPI::new().fingerprints(fingerprints).await
+}
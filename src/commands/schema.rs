@@ -0,0 +1,207 @@
+use crate::files::{manifest::Manifest, project::Project};
+use anyhow::{anyhow, Context, Result};
+use schemars::schema_for;
+use std::{
+    fs::{read_to_string, write},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct SchemaParams {
+    /// File to write the schema to instead of printing it.
+    #[structopt(parse(from_os_str))]
+    output: Option<PathBuf>,
+    /// Fail if the file is not the schema of this version of mcpacker instead of writing it.
+    #[structopt(long = "check", requires = "output")]
+    check: bool,
+}
+
+impl SchemaParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut schema = serde_json::to_string_pretty(&schema_for!(Manifest))
+            .with_context(|| "could not serialize schema")?;
+        schema.push('\n');
+        let output = match &self.output {
+            Some(output) => project.root().join(output),
+            None => {
+                print!("{}", schema);
+                return Ok(());
+            }
+        };
+        if self.check {
+            let current = read_to_string(&output).with_context(|| {
+                format!("could not open {} for reading", output.to_string_lossy())
+            })?;
+            if current != schema {
+                return Err(anyhow!(
+                    "{} is out of date, run schema to update it",
+                    output.to_string_lossy()
+                ));
+            }
+            return Ok(());
+        }
+        write(&output, schema)
+            .with_context(|| format!("could not write {}", output.to_string_lossy()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::files::manifest::Manifest;
+    use schemars::schema_for;
+    use serde_json::Value;
+
+    const SCHEMA: &str = include_str!("../../schema/manifest.schema.json");
+
+    const SAMPLE: &str = r#"
+schemaVersion: 2
+name: Sample Pack
+version: 1.0.0
+author: Me
+minecraftVersion: 1.20.1
+modLoader: forge
+modLoaderVersion: 47.2.0
+mods:
+  - projectID: 238222
+    fileID: 4712866
+    fileName: jei-1.20.1-forge-15.2.0.27.jar
+    fingerprint: 2281488291
+    fileSize: 1137734
+    name: Just Enough Items (JEI)
+    slug: jei
+    authors:
+      - mezz
+    side: both
+    metadata:
+      - id: jei
+        version: 15.2.0.27
+        side: both
+        dependencies:
+          minecraft: "[1.20.1,1.20.2)"
+includes:
+  - config/**
+excludeIncludes:
+  - config/local.toml
+profiles:
+  lite:
+    excludeMods:
+      - 238222
+"#;
+
+    #[test]
+    fn schema_file_is_up_to_date() {
+        let mut schema = serde_json::to_string_pretty(&schema_for!(Manifest)).unwrap();
+        schema.push('\n');
+        assert!(
+            schema == SCHEMA,
+            "schema/manifest.schema.json is out of date, run schema to update it"
+        );
+    }
+
+    #[test]
+    fn sample_manifest_matches_schema() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        let sample: Value = serde_yaml::from_str(SAMPLE).unwrap();
+        assert_eq!(
+            validate(&schema, &schema, &sample, "$"),
+            Vec::<String>::new()
+        );
+        let manifest: Manifest = serde_yaml::from_str(SAMPLE).unwrap();
+        let written = serde_json::to_value(&manifest).unwrap();
+        assert_eq!(
+            validate(&schema, &schema, &written, "$"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn schema_rejects_wrong_types() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        let mut sample: Value = serde_yaml::from_str(SAMPLE).unwrap();
+        sample["modLoader"] = "rift".into();
+        sample["mods"][0]["fileID"] = "latest".into();
+        let _ = sample.as_object_mut().unwrap().remove("author");
+        assert_eq!(validate(&schema, &schema, &sample, "$").len(), 3);
+    }
+
+    // Checks the parts of JSON Schema that schemars generates for the manifest. Fields
+    // missing from `properties` are errors too, so the schema can not fall behind the types.
+    fn validate(root: &Value, schema: &Value, value: &Value, path: &str) -> Vec<String> {
+        if let Some(r) = schema["$ref"].as_str() {
+            let name = r.trim_start_matches("#/definitions/");
+            return validate(root, &root["definitions"][name], value, path);
+        }
+        if let Some(any) = schema["anyOf"].as_array() {
+            if any
+                .iter()
+                .any(|s| validate(root, s, value, path).is_empty())
+            {
+                return Vec::new();
+            }
+            return vec![format!("{}: matches none of anyOf", path)];
+        }
+        let types: Vec<&str> = match &schema["type"] {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let is_type = |t: &&str| match *t {
+            "null" => value.is_null(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "string" => value.is_string(),
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            _ => false,
+        };
+        if !types.is_empty() && !types.iter().any(is_type) {
+            return vec![format!("{}: is not {}", path, types.join(" or "))];
+        }
+        if let Some(values) = schema["enum"].as_array() {
+            if !values.contains(value) {
+                return vec![format!("{}: {} is not one of {:?}", path, value, values)];
+            }
+        }
+        let mut errors = Vec::new();
+        match value {
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let item_path = format!("{}[{}]", path, i);
+                    errors.extend(validate(root, &schema["items"], item, &item_path));
+                }
+            }
+            Value::Object(map) => {
+                for required in schema["required"].as_array().into_iter().flatten() {
+                    if !map.contains_key(required.as_str().unwrap()) {
+                        errors.push(format!("{}: {} is missing", path, required));
+                    }
+                }
+                for (k, v) in map {
+                    let field_path = format!("{}.{}", path, k);
+                    match &schema["properties"][k] {
+                        Value::Null => match &schema["additionalProperties"] {
+                            Value::Bool(false) => {
+                                errors.push(format!("{}: is not allowed", field_path))
+                            }
+                            Value::Object(_) => errors.extend(validate(
+                                root,
+                                &schema["additionalProperties"],
+                                v,
+                                &field_path,
+                            )),
+                            _ if schema["properties"].is_object() => {
+                                errors.push(format!("{}: is not in the schema", field_path))
+                            }
+                            _ => {}
+                        },
+                        property => errors.extend(validate(root, property, v, &field_path)),
+                    }
+                }
+            }
+            _ => {}
+        }
+        errors
+    }
+}
@@ -1,14 +1,19 @@
 use crate::{
     files::{
-        manifest::{create_manifest_file, get_manifest, Manifest, Mod},
-        minecraft_instance::get_minecraft_instance,
-        MODS_DIR,
+        manifest::{get_instance_manifest, get_manifest, write_manifest, Manifest, Mod},
+        minecraft_instance::MINECRAFT_INSTANCE_FILE,
+        mmc_instance::MMC_PACK_FILE,
+        mod_metadata::{duplicate_mod_ids, installed_mod_jars, read_mod_metadata},
+        project::{Launcher, Project},
+    },
+    utils::{
+        murmur2::fingerprint,
+        twitch_api::{Addon, TwitchAPI},
     },
-    utils::{murmur2::murmurhash2_32, twitch_api::TwitchAPI},
 };
 use anyhow::{anyhow, Context, Result};
-use once_cell::sync::Lazy;
 use std::{
+    io::Cursor,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -21,29 +26,163 @@ use tokio::{
 };
 
 #[derive(StructOpt, Debug)]
-pub struct SyncParams {}
+pub struct SyncParams {
+    /// Launcher to read the instance from [default: the one found in the pack directory].
+    #[structopt(short = "s", long = "source", possible_values = &["curseforge", "prism", "multimc"])]
+    source: Option<Launcher>,
+}
 
 impl SyncParams {
-    pub fn run(&self) -> Result<()> {
-        let mut manifest = get_manifest()?;
-        let new_manifest: Manifest = (&get_minecraft_instance()?).into();
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let mut manifest = get_manifest(project)?;
+        let launcher = self.source.or_else(|| project.launcher()).ok_or_else(|| {
+            anyhow!(
+                "could not find {} or ../{}",
+                MINECRAFT_INSTANCE_FILE,
+                MMC_PACK_FILE
+            )
+        })?;
+        let new_manifest = get_instance_manifest(project, launcher)?;
         let _ = manifest.sync_mods(&new_manifest);
+        if new_manifest.minecraft_version < manifest.minecraft_version {
+            println!(
+                "warning: Minecraft version goes down from {} to {}",
+                manifest.minecraft_version, new_manifest.minecraft_version
+            );
+        }
+        if new_manifest.mod_loader == manifest.mod_loader
+            && new_manifest.mod_loader_version < manifest.mod_loader_version
+        {
+            println!(
+                "warning: mod loader version goes down from {} to {}",
+                manifest.mod_loader_version, new_manifest.mod_loader_version
+            );
+        }
+        manifest.minecraft_version = new_manifest.minecraft_version;
         manifest.mod_loader = new_manifest.mod_loader;
         manifest.mod_loader_version = new_manifest.mod_loader_version;
         manifest.name = new_manifest.name;
-        manifest.to_writer(create_manifest_file()?)?;
-        sync_mod_jars(manifest)?;
+        let res = sync_mod_jars(project, &manifest);
+        // Jars that failed to sync are skipped and picked up by the next sync
+        read_jar_metadata(project, &mut manifest);
+        read_mod_info(&mut manifest);
+        write_manifest(project, &manifest)?;
+        res?;
+        let duplicates = duplicate_mod_ids(&installed_mod_jars(project, &manifest)?);
+        for d in &duplicates {
+            println!("{}", d);
+        }
+        if !duplicates.is_empty() {
+            return Err(anyhow!("found {} duplicate mod IDs", duplicates.len()));
+        }
         Ok(())
     }
 }
 
+fn read_jar_metadata(project: &Project, manifest: &mut Manifest) {
+    let mods_dir = project.mods_dir();
+    for mut module in manifest.mods_without_metadata() {
+        let path = mods_dir.join(&module.file_name);
+        let disabled = mods_dir.join(format!("{}.disabled", module.file_name));
+        let path = if path.exists() { path } else { disabled };
+        if !path.exists() {
+            continue;
+        }
+        let buf = match std::fs::read(&path) {
+            Ok(buf) => buf,
+            Err(e) => {
+                println!("could not read {}: {}", path.to_string_lossy(), e);
+                continue;
+            }
+        };
+        // Only trust jars that passed verification
+        if fingerprint(&buf) != module.fingerprint {
+            continue;
+        }
+        match read_mod_metadata(Cursor::new(buf)) {
+            Ok(metadata) => {
+                module.metadata = Some(metadata);
+                let _ = manifest.update_mod(module);
+            }
+            Err(e) => println!(
+                "could not read metadata of {}: {:#}",
+                path.to_string_lossy(),
+                e
+            ),
+        }
+    }
+}
+
+// Names make the manifest readable, so a mod that can not be looked up is not an error.
+fn read_mod_info(manifest: &mut Manifest) {
+    let modules = manifest.mods_without_info();
+    if modules.is_empty() {
+        return;
+    }
+    let addons = fetch_addons(modules.iter().map(|m| m.project_id).collect());
+    // Offline every lookup fails the same way, so they are reported together
+    let mut failed = Vec::new();
+    let mut error = None;
+    for (mut module, addon) in modules.into_iter().zip(addons) {
+        match addon {
+            Ok(addon) => {
+                module.name = Some(addon.name);
+                module.slug = Some(addon.slug);
+                module.authors = Some(addon.authors.into_iter().map(|a| a.name).collect());
+                module.url = Some(addon.website_url);
+            }
+            Err(e) => {
+                failed.push(module.project_id.to_string());
+                error = error.or(Some(e));
+                if module.name.is_some() {
+                    continue;
+                }
+                // Fall back to the name the jar gives itself
+                module.name = module
+                    .metadata
+                    .iter()
+                    .flatten()
+                    .find_map(|m| m.name.clone());
+                if module.name.is_none() {
+                    continue;
+                }
+            }
+        }
+        let _ = manifest.update_mod(module);
+    }
+    if let Some(e) = error {
+        println!(
+            "could not look up projects {}, they are tried again on the next sync: {}",
+            failed.join(", "),
+            e
+        );
+    }
+}
+
 #[tokio::main]
-async fn sync_mod_jars(manifest: Manifest) -> Result<()> {
+async fn fetch_addons(projects: Vec<u32>) -> Vec<Result<Addon>> {
+    let twitch = Arc::new(TwitchAPI::new());
     let mut tasks = Vec::new();
-    if MODS_DIR.is_dir() {
-        let mut file_stream = fs::read_dir(Lazy::force(&MODS_DIR))
+    for project in projects {
+        let twitch = Arc::clone(&twitch);
+        tasks.push(task::spawn(async move { twitch.addon(project).await }));
+    }
+    let mut addons = Vec::new();
+    for t in tasks {
+        addons.push(t.await.unwrap_or_else(|e| Err(e.into())));
+    }
+    addons
+}
+
+#[tokio::main]
+async fn sync_mod_jars(project: &Project, manifest: &Manifest) -> Result<()> {
+    let mut tasks = Vec::new();
+    let mods_dir = project.mods_dir();
+    if mods_dir.is_dir() {
+        let tracked = manifest.mods_by_filename();
+        let mut file_stream = fs::read_dir(&mods_dir)
             .await
-            .with_context(|| format!("could not read directory {}", MODS_DIR.to_string_lossy()))?;
+            .with_context(|| format!("could not read directory {}", mods_dir.to_string_lossy()))?;
         while let Some(file) = file_stream.next().await {
             let file = file.with_context(|| format!("could not get information for entry"))?;
             let file_path = file.path();
@@ -55,7 +194,7 @@ async fn sync_mod_jars(manifest: Manifest) -> Result<()> {
                 continue;
             }
             let (jar, _) = jar.unwrap();
-            let m = manifest.get_mod_by_filename(&jar.file_name().unwrap().to_string_lossy());
+            let m = tracked.get(jar.file_name().unwrap().to_string_lossy().as_ref());
             match m {
                 Some(m) => tasks.push(task::spawn(verify_file(
                     fs::File::open(&file_path).await.with_context(|| {
@@ -64,28 +203,31 @@ async fn sync_mod_jars(manifest: Manifest) -> Result<()> {
                     m.clone(),
                 ))),
                 None => {
-                    if !manifest.include_exists(&file_path) {
+                    let include = file_path.strip_prefix(project.root()).unwrap_or(&file_path);
+                    if !manifest.is_included(include, false)? {
                         tasks.push(task::spawn(remove_file(file_path)))
                     }
                 }
             }
         }
     }
-    if let Some(modules) = manifest.get_mods() {
+    let modules = manifest.effective_mods();
+    if !modules.is_empty() {
         let twitch = Arc::new(TwitchAPI::new());
         for module in modules {
-            let path = MODS_DIR.join(Path::new(&module.file_name));
+            let path = mods_dir.join(Path::new(&module.file_name));
             if path.exists() {
                 continue;
             }
             let mut disabled_path = module.file_name.clone();
             disabled_path.push_str(".disabled");
-            let disabled_path = MODS_DIR.join(Path::new(&disabled_path));
+            let disabled_path = mods_dir.join(Path::new(&disabled_path));
             if disabled_path.exists() {
                 continue;
             }
             tasks.push(task::spawn(download_mod(
                 Arc::clone(&twitch),
+                mods_dir.clone(),
                 module.clone(),
             )));
         }
@@ -127,8 +269,7 @@ async fn verify_file(mut file: fs::File, module: Mod) -> Result<()> {
     }
     // Compute the hash using the original Murmur2 32 bit algorithm.
     // This hash function does not support streaming bytes so we need the full buff.
-    buf.retain(is_not_whitespace);
-    let h = murmurhash2_32(buf.as_ref(), 1);
+    let h = fingerprint(&buf);
     if h != module.fingerprint {
         return Err(anyhow!(format!(
             "{} is not valid, expected hash {} got {}",
@@ -138,20 +279,15 @@ async fn verify_file(mut file: fs::File, module: Mod) -> Result<()> {
     Ok(())
 }
 
-fn is_not_whitespace(b: &u8) -> bool {
-    let b = *b;
-    b != 9 && b != 10 && b != 13 && b != 32
-}
-
 async fn remove_file(orig: PathBuf) -> Result<()> {
     Ok(fs::remove_file(&orig)
         .await
         .with_context(|| format!("could not remove file {}", orig.to_string_lossy()))?)
 }
 
-async fn download_mod(twitch: Arc<TwitchAPI>, module: Mod) -> Result<()> {
-    fs::create_dir_all(Lazy::force(&MODS_DIR)).await?;
-    let path = MODS_DIR.join(Path::new(&module.file_name));
+async fn download_mod(twitch: Arc<TwitchAPI>, mods_dir: PathBuf, module: Mod) -> Result<()> {
+    fs::create_dir_all(&mods_dir).await?;
+    let path = mods_dir.join(Path::new(&module.file_name));
     // Want to make sure the file handle is closed before verifying the file
     let f = fs::OpenOptions::new()
         .truncate(true)
@@ -174,9 +310,9 @@ async fn download_mod(twitch: Arc<TwitchAPI>, module: Mod) -> Result<()> {
     verify_file(f, module).await
 }
 
-fn jar_name(p: &PathBuf) -> Option<(PathBuf, bool)> {
+pub fn jar_name(p: &Path) -> Option<(PathBuf, bool)> {
     match p.extension() {
-        Some(ext) if ext == "jar" => Some((p.clone(), false)),
+        Some(ext) if ext == "jar" => Some((p.to_path_buf(), false)),
         Some(ext) if ext == "disabled" => {
             let parent = p.parent();
             let file_stem = p.file_stem();
@@ -0,0 +1,12 @@
+use crate::files::{manifest::restore_manifest_backup, project::Project};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct UndoParams {}
+
+impl UndoParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        restore_manifest_backup(project)
+    }
+}
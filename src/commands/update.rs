@@ -0,0 +1,148 @@
+use crate::{
+    files::{
+        lockfile::{is_split, mod_keys, Lock, ModKey, SPLIT_MANIFEST_STEM},
+        manifest::{migrate, write_lock, Mod},
+        manifest_format::{from_yaml, ManifestFormat},
+        minecraft_instance::InstalledAddon,
+        mod_loader::{self, ModLoader},
+        project::Project,
+        versions::MinecraftVersion,
+    },
+    utils::twitch_api::{ProjectFile, TwitchAPI},
+};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::{fs::read_to_string, sync::Arc};
+use structopt::StructOpt;
+use tokio::task;
+
+#[derive(StructOpt, Debug)]
+pub struct UpdateParams {
+    /// Resolve every mod to its newest file, not only the ones missing from the lock file.
+    #[structopt(long = "all")]
+    all: bool,
+}
+
+// What files have to be made for, read from the manifest without its mods.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Target {
+    minecraft_version: MinecraftVersion,
+    #[serde(default, deserialize_with = "mod_loader::deserialize_optional")]
+    mod_loader: Option<ModLoader>,
+}
+
+impl UpdateParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let path = project.manifest_file();
+        if !is_split(path) {
+            return Err(anyhow!(
+                "{} has no lock file, only {}.yaml, .toml or .json manifests do",
+                path.to_string_lossy(),
+                SPLIT_MANIFEST_STEM
+            ));
+        }
+        let text = read_to_string(path)
+            .with_context(|| format!("could not open {} for reading", path.to_string_lossy()))?;
+        let mut doc = ManifestFormat::from_path(path)?
+            .to_yaml(&text)
+            .with_context(|| format!("could not read {}", path.to_string_lossy()))?;
+        let _ = migrate(&mut doc)?;
+        let target: Target = from_yaml(&doc)
+            .with_context(|| format!("could not read {}", path.to_string_lossy()))?;
+        let keys = mod_keys(&mut doc);
+        let mut lock = Lock::read(&project.lock_file())?;
+        lock.retain(&keys);
+        let outdated: Vec<ModKey> = keys
+            .into_iter()
+            .filter(|k| self.all || lock.find(k).is_none())
+            .collect();
+        if outdated.is_empty() {
+            println!("every mod is in {}", project.lock_file().to_string_lossy());
+        }
+        for m in resolve_mods(outdated, target)? {
+            match &m.slug {
+                Some(slug) => println!("{} ({}) resolved to {}", slug, m.project_id, m.file_name),
+                None => println!("{} resolved to {}", m.project_id, m.file_name),
+            }
+            lock.insert(&m)?;
+        }
+        write_lock(project, &lock)
+    }
+}
+
+#[tokio::main]
+async fn resolve_mods(mod_keys: Vec<ModKey>, target: Target) -> Result<Vec<Mod>> {
+    let twitch = Arc::new(TwitchAPI::new());
+    let mut tasks = Vec::new();
+    for mod_key in mod_keys {
+        let twitch = Arc::clone(&twitch);
+        let target = target.clone();
+        tasks.push(task::spawn(async move {
+            let project = match (mod_key.project_id, &mod_key.slug) {
+                (Some(id), _) => id,
+                (None, Some(slug)) => twitch.addon_by_slug(slug).await?.id,
+                (None, None) => return Err(anyhow!("mod has no projectID or slug")),
+            };
+            let file = newest_file(twitch.files(project).await?, &target).ok_or_else(|| {
+                anyhow!(
+                    "project {} has no file for Minecraft {}",
+                    project,
+                    target.minecraft_version
+                )
+            })?;
+            let mut module: Mod = (&InstalledAddon {
+                addon_id: project,
+                installed_file: file.file,
+            })
+                .into();
+            module.slug = mod_key.slug;
+            Ok::<Mod, anyhow::Error>(module)
+        }));
+    }
+    let mut mods = Vec::new();
+    let mut was_error = false;
+    for t in tasks {
+        match t.await {
+            Ok(Ok(m)) => mods.push(m),
+            Ok(Err(e)) => {
+                was_error = true;
+                println!("{}", e)
+            }
+            Err(e) => {
+                was_error = true;
+                println!("{}", e)
+            }
+        };
+    }
+    if was_error {
+        return Err(anyhow!("there was an error resolving mods"));
+    }
+    Ok(mods)
+}
+
+// Files only name a mod loader when they are made for specific ones.
+fn newest_file(files: Vec<ProjectFile>, target: &Target) -> Option<ProjectFile> {
+    let minecraft_version = target.minecraft_version.to_string();
+    files
+        .into_iter()
+        .filter(|f| f.game_version.contains(&minecraft_version))
+        .filter(|f| {
+            let loaders: Vec<&String> = f
+                .game_version
+                .iter()
+                .filter(|v| {
+                    ModLoader::ALL
+                        .iter()
+                        .any(|l| v.eq_ignore_ascii_case(l.name()))
+                })
+                .collect();
+            match target.mod_loader {
+                Some(loader) if !loaders.is_empty() => loaders
+                    .iter()
+                    .any(|v| v.eq_ignore_ascii_case(loader.name())),
+                _ => true,
+            }
+        })
+        .max_by(|a, b| a.file_date.cmp(&b.file_date))
+}
@@ -0,0 +1,538 @@
+use crate::{
+    files::{
+        lockfile::is_split,
+        manifest::{get_manifest, migrate, read_manifest_file, Manifest, SCHEMA_VERSION},
+        manifest_format::ManifestFormat,
+        project::Project,
+    },
+    utils::patterns::is_pattern,
+};
+use anyhow::{anyhow, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::read_to_string,
+    io::stdout,
+    path::{Component, Path, PathBuf},
+};
+use structopt::StructOpt;
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+    Yaml,
+};
+
+#[derive(StructOpt, Debug)]
+pub struct ValidateParams {
+    /// Print the problems as JSON for editors and scripts.
+    #[structopt(long = "json")]
+    json: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem with the manifest and where it is.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Problem {
+    severity: Severity,
+    message: String,
+    /// Field path like `mods[1].fileName`, as serde_yaml names them.
+    path: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+// Only the parts of the manifest that are checked, read as written so
+// duplicates are not merged like they are in Manifest.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RawManifest {
+    extends: Option<PathBuf>,
+    exclude_includes: Option<Vec<String>>,
+    includes: Option<Vec<String>>,
+    mods: Option<Vec<RawMod>>,
+    profiles: Option<BTreeMap<String, RawProfile>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RawMod {
+    /// mcpacker.yaml manifests can name mods by slug instead.
+    #[serde(rename = "projectID")]
+    project_id: Option<u32>,
+    /// Kept in the lock file for mcpacker.yaml manifests.
+    file_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RawProfile {
+    exclude_includes: Option<Vec<String>>,
+    includes: Option<Vec<String>>,
+    mods: Option<Vec<RawMod>>,
+}
+
+impl ValidateParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let file = project.manifest_file();
+        let text = read_to_string(file)
+            .with_context(|| format!("could not open {} for reading", file.to_string_lossy()))?;
+        let format = ManifestFormat::from_path(file)?;
+        // JSON is also YAML so it has positions too
+        let positions = match format {
+            ManifestFormat::Toml => HashMap::new(),
+            _ => value_positions(&text),
+        };
+        let mut problems = Vec::new();
+        check_manifest(project, &text, format, &mut problems);
+        for p in &mut problems {
+            if p.line.is_none() {
+                if let Some((line, column)) = p.path.as_ref().and_then(|p| positions.get(p)) {
+                    p.line = Some(*line);
+                    p.column = Some(*column);
+                }
+            }
+        }
+        let errors = problems
+            .iter()
+            .filter(|p| p.severity == Severity::Error)
+            .count();
+        if self.json {
+            serde_json::to_writer_pretty(stdout(), &problems)
+                .with_context(|| "could not serialize problems")?;
+            println!();
+        } else {
+            for p in &problems {
+                print_problem(file, p);
+            }
+            if problems.is_empty() {
+                println!("{} is valid", file.to_string_lossy());
+            }
+        }
+        if errors > 0 {
+            return Err(anyhow!("found {} errors", errors));
+        }
+        Ok(())
+    }
+}
+
+// Prints problems like compilers do so editors can jump to them.
+fn print_problem(file: &Path, p: &Problem) {
+    let location = match (p.line, p.column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", file.to_string_lossy(), line, column),
+        _ => file.to_string_lossy().into(),
+    };
+    let severity = match p.severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    };
+    match &p.path {
+        Some(path) => println!("{}: {}: {}: {}", location, severity, path, p.message),
+        None => println!("{}: {}: {}", location, severity, p.message),
+    }
+}
+
+fn check_manifest(
+    project: &Project,
+    text: &str,
+    format: ManifestFormat,
+    problems: &mut Vec<Problem>,
+) {
+    // Broken documents are reported below with their location
+    let mut doc = format.to_yaml(text).unwrap_or(Yaml::Null);
+    if !doc.is_null() {
+        match migrate(&mut doc) {
+            Ok(from) if from < SCHEMA_VERSION => problems.push(Problem::warning(
+                format!(
+                    "schema version {} is older than {}, run migrate to upgrade it",
+                    from, SCHEMA_VERSION
+                ),
+                Some("schemaVersion"),
+            )),
+            Ok(_) => {}
+            Err(e) => {
+                problems.push(Problem::error(format!("{:#}", e), Some("schemaVersion")));
+                return;
+            }
+        }
+    }
+    let raw = match parse::<RawManifest>(text, format) {
+        Ok(raw) => raw,
+        Err(p) => {
+            problems.push(p);
+            return;
+        }
+    };
+    // Mods of split manifests are only complete together with the lock file
+    let typed = if is_split(project.manifest_file()) {
+        read_manifest_file(project.manifest_file())
+            .map(|_| ())
+            .map_err(|e| Problem::error(format!("{:#}", e), None))
+    } else {
+        parse::<Manifest>(text, format).map(|_| ())
+    };
+    if let Err(p) = typed {
+        problems.push(p);
+        return;
+    }
+    check_mods("mods", raw.mods.as_deref().unwrap_or_default(), problems);
+    // Excludes can name files of the extended manifests, which are in their own directories
+    let own = vec![project.root().to_path_buf()];
+    let layers = match get_manifest(project) {
+        Ok(m) => m.layers().iter().map(|l| l.dir().to_path_buf()).collect(),
+        // A missing extended manifest is reported below
+        Err(_) => own.clone(),
+    };
+    let mut includes = vec![
+        (
+            "includes".to_string(),
+            raw.includes.unwrap_or_default(),
+            &own,
+        ),
+        (
+            "excludeIncludes".to_string(),
+            raw.exclude_includes.unwrap_or_default(),
+            &layers,
+        ),
+    ];
+    for (name, profile) in raw.profiles.unwrap_or_default() {
+        check_mods(
+            &format!("profiles.{}.mods", name),
+            profile.mods.as_deref().unwrap_or_default(),
+            problems,
+        );
+        includes.push((
+            format!("profiles.{}.includes", name),
+            profile.includes.unwrap_or_default(),
+            &own,
+        ));
+        includes.push((
+            format!("profiles.{}.excludeIncludes", name),
+            profile.exclude_includes.unwrap_or_default(),
+            &layers,
+        ));
+    }
+    for (field, list, roots) in &includes {
+        check_includes(roots, field, list, problems);
+    }
+    if let Some(extends) = raw.extends {
+        let base = project
+            .manifest_file()
+            .parent()
+            .map(|p| p.join(&extends))
+            .unwrap_or_else(|| extends.clone());
+        if !base.is_file() {
+            problems.push(Problem::error(
+                format!("{} does not exist", base.to_string_lossy()),
+                Some("extends"),
+            ));
+        }
+    }
+}
+
+// JSON is read as YAML since serde_yaml errors have the path of the value.
+fn parse<T: DeserializeOwned>(text: &str, format: ManifestFormat) -> Result<T, Problem> {
+    match format {
+        ManifestFormat::Toml => toml::from_str(text).map_err(|e| Problem::from_toml(&e)),
+        _ => serde_yaml::from_str(text).map_err(|e| Problem::from_yaml(&e)),
+    }
+}
+
+fn check_mods(field: &str, mods: &[RawMod], problems: &mut Vec<Problem>) {
+    let mut project_ids: HashMap<u32, usize> = HashMap::new();
+    let mut file_names: HashMap<&str, usize> = HashMap::new();
+    for (i, m) in mods.iter().enumerate() {
+        if let Some(project_id) = m.project_id {
+            if let Some(first) = project_ids.get(&project_id) {
+                problems.push(Problem::error(
+                    format!(
+                        "projectID {} is also used by {}[{}]",
+                        project_id, field, first
+                    ),
+                    Some(&format!("{}[{}].projectID", field, i)),
+                ));
+            } else {
+                let _ = project_ids.insert(project_id, i);
+            }
+        }
+        let file_name = match &m.file_name {
+            Some(f) => f,
+            None => continue,
+        };
+        if let Some(first) = file_names.get(file_name.as_str()) {
+            problems.push(Problem::error(
+                format!(
+                    "fileName {} is also used by {}[{}]",
+                    file_name, field, first
+                ),
+                Some(&format!("{}[{}].fileName", field, i)),
+            ));
+        } else {
+            let _ = file_names.insert(file_name, i);
+        }
+    }
+}
+
+// Includes only need to exist in one of `roots`.
+fn check_includes(
+    roots: &[PathBuf],
+    field: &str,
+    includes: &[String],
+    problems: &mut Vec<Problem>,
+) {
+    for (i, include) in includes.iter().enumerate() {
+        let path = format!("{}[{}]", field, i);
+        // A leading / anchors the pattern to the pack directory
+        let include = include.strip_prefix('/').unwrap_or(include);
+        let inside = Path::new(include)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if !inside {
+            problems.push(Problem::error(
+                format!("{} is outside of the pack directory", include),
+                Some(&path),
+            ));
+        } else if !is_pattern(include) && !roots.iter().any(|r| r.join(include).exists()) {
+            problems.push(Problem::warning(
+                format!("{} does not exist", include),
+                Some(&path),
+            ));
+        }
+    }
+}
+
+impl Problem {
+    fn error(message: String, path: Option<&str>) -> Self {
+        Problem {
+            severity: Severity::Error,
+            message,
+            path: path.map(String::from),
+            line: None,
+            column: None,
+        }
+    }
+
+    fn warning(message: String, path: Option<&str>) -> Self {
+        Problem {
+            severity: Severity::Warning,
+            ..Problem::error(message, path)
+        }
+    }
+
+    fn from_yaml(e: &serde_yaml::Error) -> Self {
+        let mut message = e.to_string();
+        let location = e.location();
+        if let Some(l) = &location {
+            let suffix = format!(" at line {} column {}", l.line(), l.column());
+            if message.ends_with(&suffix) {
+                message.truncate(message.len() - suffix.len());
+            }
+        }
+        // Errors inside the document start with the path of the value, like `mods[1].fileSize: `
+        let path = match message.find(": ") {
+            Some(i) if !message[..i].contains(' ') => {
+                let path = message[..i].to_string();
+                message = message[i + 2..].to_string();
+                Some(path)
+            }
+            _ => None,
+        };
+        Problem {
+            severity: Severity::Error,
+            message,
+            path,
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+        }
+    }
+
+    fn from_toml(e: &toml::de::Error) -> Self {
+        let mut message = e.to_string();
+        let location = e.line_col().map(|(line, column)| (line + 1, column + 1));
+        if let Some((line, column)) = location {
+            let suffix = format!(" at line {} column {}", line, column);
+            if message.ends_with(&suffix) {
+                message.truncate(message.len() - suffix.len());
+            }
+        }
+        // Errors in a value end with its key, like ` for key `mods.fileSize``
+        let path = match message.rfind(" for key `") {
+            Some(i) if message.ends_with('`') => {
+                let path = message[i + 10..message.len() - 1].to_string();
+                message.truncate(i);
+                Some(path)
+            }
+            _ => None,
+        };
+        Problem {
+            severity: Severity::Error,
+            message,
+            path,
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+        }
+    }
+}
+
+// Finds where every value in a YAML document starts, by the same paths serde_yaml uses.
+fn value_positions(text: &str) -> HashMap<String, (usize, usize)> {
+    let mut positions = Positions::default();
+    // Broken documents are reported by serde_yaml, positions up to the error are still useful
+    let _ = Parser::new(text.chars()).load(&mut positions, false);
+    positions.found
+}
+
+#[derive(Debug)]
+enum Frame {
+    Seq { path: String, index: usize },
+    Map { path: String, key: Option<String> },
+}
+
+#[derive(Debug, Default)]
+struct Positions {
+    stack: Vec<Frame>,
+    found: HashMap<String, (usize, usize)>,
+}
+
+impl MarkedEventReceiver for Positions {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        let is_node = matches!(
+            ev,
+            Event::Scalar(..) | Event::SequenceStart(_) | Event::MappingStart(_) | Event::Alias(_)
+        );
+        if matches!(ev, Event::SequenceEnd | Event::MappingEnd) {
+            let _ = self.stack.pop();
+            return;
+        }
+        if !is_node {
+            return;
+        }
+        let path = match self.stack.last_mut() {
+            None => ".".to_string(),
+            Some(Frame::Map {
+                key: key @ None, ..
+            }) => {
+                *key = Some(match &ev {
+                    Event::Scalar(s, ..) => s.clone(),
+                    _ => "?".to_string(),
+                });
+                // Keys that are mappings or sequences are not tracked
+                match ev {
+                    Event::SequenceStart(_) => self.stack.push(Frame::Seq {
+                        path: "?".to_string(),
+                        index: 0,
+                    }),
+                    Event::MappingStart(_) => self.stack.push(Frame::Map {
+                        path: "?".to_string(),
+                        key: None,
+                    }),
+                    _ => {}
+                }
+                return;
+            }
+            Some(Frame::Map { path, key }) => {
+                let key = key.take().unwrap_or_default();
+                if path == "." {
+                    key
+                } else {
+                    format!("{}.{}", path, key)
+                }
+            }
+            Some(Frame::Seq { path, index }) => {
+                *index += 1;
+                format!("{}[{}]", path, *index - 1)
+            }
+        };
+        let _ = self
+            .found
+            .insert(path.clone(), (mark.line(), mark.col() + 1));
+        match ev {
+            Event::SequenceStart(_) => self.stack.push(Frame::Seq { path, index: 0 }),
+            Event::MappingStart(_) => self.stack.push(Frame::Map { path, key: None }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_dir::TestDir;
+
+    const HEADER: &str =
+        "schemaVersion: 2\nname: t\nversion: 1.0.0\nauthor: t\nminecraftVersion: 1.20.1\nmodLoaderVersion: 47.2.0\n";
+
+    fn include_problems(include: &str) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        check_includes(
+            &[PathBuf::from("pack")],
+            "includes",
+            &[include.to_string()],
+            &mut problems,
+        );
+        problems
+    }
+
+    #[test]
+    fn anchored_includes_are_inside_the_pack() {
+        assert!(include_problems("/config/*.cfg").is_empty());
+        assert!(include_problems("config/**").is_empty());
+    }
+
+    #[test]
+    fn escaping_includes_are_errors() {
+        for include in &["../other/*.cfg", "/../other/*.cfg", "config/../../*.cfg"] {
+            let problems = include_problems(include);
+            assert_eq!(problems.len(), 1, "{}", include);
+            assert_eq!(problems[0].severity, Severity::Error);
+        }
+    }
+
+    #[test]
+    fn excludes_of_derived_manifests_exist_in_the_base() {
+        let dir = TestDir::new("validate-derived");
+        let _ = dir.write(
+            "base/.manifest.yaml",
+            &format!("{}includes:\n  - options.txt\n", HEADER),
+        );
+        let _ = dir.write("base/options.txt", "");
+        let text = format!(
+            "{}extends: ../base/.manifest.yaml\nexcludeIncludes:\n  - options.txt\n  - servers.dat\n",
+            HEADER
+        );
+        let manifest = dir.write("derived/.manifest.yaml", &text);
+        let project = Project::new(Some(manifest.parent().unwrap()), None).unwrap();
+        let mut problems = Vec::new();
+        check_manifest(&project, &text, ManifestFormat::Yaml, &mut problems);
+        let paths: Vec<_> = problems.iter().map(|p| p.path.as_deref()).collect();
+        assert_eq!(paths, vec![Some("excludeIncludes[1]")]);
+    }
+
+    #[test]
+    fn profile_mods_are_checked_for_duplicates() {
+        let dir = TestDir::new("validate-profile");
+        let m = "{projectID: 1, fileID: 1, fileName: a.jar, fingerprint: 0, fileSize: 0}";
+        let text = format!(
+            "{}profiles:\n  lite:\n    mods:\n      - {}\n      - {}\n",
+            HEADER, m, m
+        );
+        let _ = dir.write(".manifest.yaml", &text);
+        let project = Project::new(Some(dir.path()), None).unwrap();
+        let mut problems = Vec::new();
+        check_manifest(&project, &text, ManifestFormat::Yaml, &mut problems);
+        let paths: Vec<_> = problems.iter().map(|p| p.path.as_deref()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Some("profiles.lite.mods[1].projectID"),
+                Some("profiles.lite.mods[1].fileName")
+            ]
+        );
+    }
+}
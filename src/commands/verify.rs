@@ -0,0 +1,62 @@
+use crate::{
+    files::{
+        manifest::get_manifest,
+        mod_metadata::{duplicate_mod_ids, installed_mod_jars},
+        project::Project,
+    },
+    utils::murmur2::fingerprint,
+};
+use anyhow::{anyhow, Context, Result};
+use std::fs::read;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct VerifyParams {}
+
+impl VerifyParams {
+    pub fn run(&self, project: &Project) -> Result<()> {
+        let manifest = get_manifest(project)?;
+        let mods_dir = project.mods_dir();
+        let mut problems = Vec::new();
+        let modules = manifest.effective_mods();
+        for module in &modules {
+            let path = mods_dir.join(&module.file_name);
+            let disabled = mods_dir.join(format!("{}.disabled", module.file_name));
+            let path = if path.exists() { path } else { disabled };
+            if !path.exists() {
+                problems.push(format!(
+                    "{} of project {} is missing, run sync to download it",
+                    module.file_name, module.project_id
+                ));
+                continue;
+            }
+            let buf = read(&path)
+                .with_context(|| format!("could not read {}", path.to_string_lossy()))?;
+            if buf.len() as u64 != module.file_size {
+                problems.push(format!(
+                    "{} is not valid, expected length {} got {}",
+                    module.file_name,
+                    module.file_size,
+                    buf.len()
+                ));
+                continue;
+            }
+            let h = fingerprint(&buf);
+            if h != module.fingerprint {
+                problems.push(format!(
+                    "{} is not valid, expected hash {} got {}",
+                    module.file_name, module.fingerprint, h
+                ));
+            }
+        }
+        problems.extend(duplicate_mod_ids(&installed_mod_jars(project, &manifest)?));
+        for p in &problems {
+            println!("{}", p);
+        }
+        if !problems.is_empty() {
+            return Err(anyhow!("found {} problems", problems.len()));
+        }
+        println!("verified {} mods", modules.len());
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+use crate::files::{
+    manifest::Mod,
+    manifest_format::{ManifestFormat, YamlTree},
+};
+use anyhow::{anyhow, Context, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    ffi::OsStr,
+    fmt,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+use yaml_rust::{yaml::Hash, Yaml};
+
+pub const LOCK_FILE: &str = "mcpacker.lock";
+
+/// Manifests named like this keep the files their mods resolved to in `LOCK_FILE`.
+pub const SPLIT_MANIFEST_STEM: &str = "mcpacker";
+
+const LOCK_HEADER: &str = "# Written by mcpacker sync and update, do not edit.\n";
+
+// Fields of a mod that come from CurseForge or the jar instead of being written by hand.
+const RESOLVED_FIELDS: [&str; 5] = ["fileID", "fileName", "fingerprint", "fileSize", "metadata"];
+
+// Fields a mod can be written by, copied to the lock to find it again.
+const KEY_FIELDS: [&str; 2] = ["projectID", "slug"];
+
+/// Whether the manifest at `path` keeps its resolved mods in a lock file next to it.
+pub fn is_split(path: &Path) -> bool {
+    path.file_stem() == Some(OsStr::new(SPLIT_MANIFEST_STEM))
+}
+
+pub fn lock_path(manifest: &Path) -> PathBuf {
+    manifest.with_file_name(LOCK_FILE)
+}
+
+/// How a mod is written in the manifest, by project ID or by its slug on CurseForge.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ModKey {
+    pub project_id: Option<u32>,
+    pub slug: Option<String>,
+}
+
+impl fmt::Display for ModKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.project_id, &self.slug) {
+            (Some(id), _) => write!(f, "{}", id),
+            (None, Some(slug)) => f.write_str(slug),
+            (None, None) => f.write_str("?"),
+        }
+    }
+}
+
+impl ModKey {
+    fn of(m: &Hash) -> Option<Self> {
+        let key = ModKey {
+            project_id: m.get(&key("projectID")).and_then(as_project_id),
+            slug: m
+                .get(&key("slug"))
+                .and_then(Yaml::as_str)
+                .map(str::to_string),
+        };
+        if key.project_id.is_none() && key.slug.is_none() {
+            None
+        } else {
+            Some(key)
+        }
+    }
+}
+
+/// Resolved fields of mods by project ID, as kept in `mcpacker.lock`.
+///
+/// A project can only resolve to one file, even when profiles list it again.
+/// Mods written by slug are found by the slug kept with their entry.
+#[derive(Debug, Default)]
+pub struct Lock {
+    mods: BTreeMap<u32, Hash>,
+}
+
+impl Lock {
+    /// Reads the lock file, which is empty when it does not exist yet.
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Lock::default());
+        }
+        let text = read_to_string(path)
+            .with_context(|| format!("could not open {} for reading", path.to_string_lossy()))?;
+        let doc = ManifestFormat::Yaml
+            .to_yaml(&text)
+            .with_context(|| format!("could not read {}", path.to_string_lossy()))?;
+        let mut lock = Lock::default();
+        for entry in doc["mods"].as_vec().into_iter().flatten() {
+            let (id, entry) = match (project_id(entry), entry) {
+                (Some(id), Yaml::Hash(entry)) => (id, entry),
+                _ => {
+                    return Err(anyhow!(
+                        "{} has a mod without a projectID",
+                        path.to_string_lossy()
+                    ))
+                }
+            };
+            let _ = lock.mods.insert(id, entry.clone());
+        }
+        Ok(lock)
+    }
+
+    pub fn serialize(&self) -> Result<String> {
+        let mods = self.mods.values().cloned().map(Yaml::Hash).collect();
+        let mut doc = Hash::new();
+        let _ = doc.insert(key("mods"), Yaml::Array(mods));
+        let body = ManifestFormat::Yaml
+            .serialize(&YamlTree(&Yaml::Hash(doc)))
+            .with_context(|| "could not serialize lock file")?;
+        Ok(format!("{}{}", LOCK_HEADER, body))
+    }
+
+    /// Moves the resolved fields of every mod in the manifest document into a new lock.
+    pub fn split(doc: &mut Yaml) -> Self {
+        let mut lock = Lock::default();
+        for m in mod_entries(doc) {
+            let id = match m.get(&key("projectID")).and_then(as_project_id) {
+                Some(id) => id,
+                None => continue,
+            };
+            let entry = lock.mods.entry(id).or_insert_with(Hash::new);
+            for field in &KEY_FIELDS {
+                if let Some(v) = m.get(&key(field)) {
+                    let _ = entry.insert(key(field), v.clone());
+                }
+            }
+            for field in &RESOLVED_FIELDS {
+                if let Some(v) = m.remove(&key(field)) {
+                    let _ = entry.insert(key(field), v);
+                }
+            }
+        }
+        lock
+    }
+
+    /// Fills in the resolved fields of every mod in the manifest document.
+    ///
+    /// Returns the mods that are not in the lock. Mods that still have their
+    /// resolved fields, like in a manifest that was just renamed, are kept.
+    pub fn join(&self, doc: &mut Yaml) -> Vec<ModKey> {
+        let mut missing = Vec::new();
+        for m in mod_entries(doc) {
+            if m.contains_key(&key("fileID")) {
+                continue;
+            }
+            let mod_key = match ModKey::of(m) {
+                Some(k) => k,
+                // Left for deserializing to report
+                None => continue,
+            };
+            match self.find(&mod_key) {
+                Some(entry) => {
+                    if !m.contains_key(&key("projectID")) {
+                        let _ = m.insert(key("projectID"), entry[&key("projectID")].clone());
+                    }
+                    for field in &RESOLVED_FIELDS {
+                        if let Some(v) = entry.get(&key(field)) {
+                            let _ = m.insert(key(field), v.clone());
+                        }
+                    }
+                }
+                None => missing.push(mod_key),
+            }
+        }
+        missing
+    }
+
+    /// The entry of the mod, by its project ID or else by its slug.
+    pub fn find(&self, mod_key: &ModKey) -> Option<&Hash> {
+        match mod_key.project_id {
+            Some(id) => self.mods.get(&id),
+            None => self
+                .mods
+                .values()
+                .find(|e| e.get(&key("slug")).and_then(Yaml::as_str) == mod_key.slug.as_deref()),
+        }
+    }
+
+    /// Adds the resolved fields of `module`, replacing the ones of its project.
+    pub fn insert(&mut self, module: &Mod) -> Result<()> {
+        let doc = ManifestFormat::Yaml.to_yaml(&ManifestFormat::Yaml.serialize(module)?)?;
+        let mut entry = Hash::new();
+        for field in KEY_FIELDS.iter().chain(RESOLVED_FIELDS.iter()) {
+            if !doc[*field].is_badvalue() {
+                let _ = entry.insert(key(field), doc[*field].clone());
+            }
+        }
+        let _ = self.mods.insert(module.project_id, entry);
+        Ok(())
+    }
+
+    /// Drops the mods that are not in `mod_keys` anymore.
+    pub fn retain(&mut self, mod_keys: &BTreeSet<ModKey>) {
+        let keep: Vec<u32> = mod_keys
+            .iter()
+            .filter_map(|k| self.find(k))
+            .filter_map(|e| e.get(&key("projectID")).and_then(as_project_id))
+            .collect();
+        self.mods.retain(|id, _| keep.contains(id));
+    }
+}
+
+/// Every mod in the manifest document, including the ones of profiles.
+pub fn mod_keys(doc: &mut Yaml) -> BTreeSet<ModKey> {
+    mod_entries(doc)
+        .into_iter()
+        .filter_map(|m| ModKey::of(m))
+        .collect()
+}
+
+// Mods are listed at the top and in every profile.
+fn mod_entries(doc: &mut Yaml) -> Vec<&mut Hash> {
+    let mut entries = Vec::new();
+    if let Yaml::Hash(map) = doc {
+        for (k, v) in map.iter_mut() {
+            match k.as_str() {
+                Some("mods") => push_mods(v, &mut entries),
+                Some("profiles") => {
+                    if let Yaml::Hash(profiles) = v {
+                        for (_, profile) in profiles.iter_mut() {
+                            if let Yaml::Hash(profile) = profile {
+                                if let Some(mods) = profile.get_mut(&key("mods")) {
+                                    push_mods(mods, &mut entries);
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    entries
+}
+
+fn push_mods<'a>(mods: &'a mut Yaml, entries: &mut Vec<&'a mut Hash>) {
+    if let Yaml::Array(mods) = mods {
+        for m in mods {
+            if let Yaml::Hash(m) = m {
+                entries.push(m);
+            }
+        }
+    }
+}
+
+fn project_id(entry: &Yaml) -> Option<u32> {
+    as_project_id(&entry["projectID"])
+}
+
+fn as_project_id(v: &Yaml) -> Option<u32> {
+    v.as_i64().and_then(|id| u32::try_from(id).ok())
+}
+
+fn key(k: &str) -> Yaml {
+    Yaml::String(k.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_dir::TestDir;
+
+    const MANIFEST: &str = r#"
+name: t
+mods:
+  - projectID: 238222
+    slug: jei
+    fileID: 4712866
+    fileName: jei.jar
+    fingerprint: 1
+    fileSize: 2
+    metadata:
+      - id: jei
+profiles:
+  lite:
+    mods:
+      - projectID: 6
+        fileID: 5
+        fileName: sodium.jar
+        fingerprint: 3
+        fileSize: 4
+"#;
+
+    fn doc(text: &str) -> Yaml {
+        ManifestFormat::Yaml.to_yaml(text).unwrap()
+    }
+
+    fn ids(keys: Vec<ModKey>) -> Vec<String> {
+        keys.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn split_and_join_round_trip() {
+        let original = doc(MANIFEST);
+        let mut intent = original.clone();
+        let lock = Lock::split(&mut intent);
+        assert!(intent["mods"][0]["fileID"].is_badvalue());
+        assert_eq!(intent["mods"][0]["slug"].as_str(), Some("jei"));
+        // Through the file, like the next command reads it
+        let dir = TestDir::new("lock-round-trip");
+        let path = dir.write(LOCK_FILE, &lock.serialize().unwrap());
+        let lock = Lock::read(&path).unwrap();
+        assert_eq!(lock.join(&mut intent), Vec::new());
+        assert_eq!(intent, original);
+    }
+
+    #[test]
+    fn join_finds_mods_by_slug() {
+        let mut resolved = doc(MANIFEST);
+        let lock = Lock::split(&mut resolved);
+        let mut intent = doc("mods:\n  - slug: jei\n  - slug: jei-old\n");
+        assert_eq!(ids(lock.join(&mut intent)), vec!["jei-old"]);
+        assert_eq!(intent["mods"][0]["projectID"].as_i64(), Some(238222));
+        assert_eq!(intent["mods"][0]["fileName"].as_str(), Some("jei.jar"));
+        assert!(intent["mods"][1]["fileName"].is_badvalue());
+    }
+
+    #[test]
+    fn retain_drops_removed_mods() {
+        let mut resolved = doc(MANIFEST);
+        let mut lock = Lock::split(&mut resolved);
+        let mut intent = doc("mods:\n  - slug: jei\n  - projectID: 7\n");
+        lock.retain(&mod_keys(&mut intent));
+        assert_eq!(lock.mods.keys().collect::<Vec<_>>(), vec![&238222]);
+        assert_eq!(ids(lock.join(&mut intent)), vec!["7"]);
+        lock.retain(&mod_keys(&mut doc("mods:\n  - slug: jei-old\n")));
+        assert!(lock.mods.is_empty());
+    }
+}
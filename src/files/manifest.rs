@@ -1,78 +1,544 @@
 use crate::{
-    files::minecraft_instance::{InstalledAddon, MinecraftInstance},
-    utils::compare::{compare, Side},
+    files::{
+        lockfile::{is_split, lock_path, Lock},
+        manifest_format::{from_yaml, ManifestFormat, YamlTree},
+        manifest_json::ManifestJson,
+        minecraft_instance::{get_minecraft_instance, InstalledAddon, MinecraftInstance},
+        mmc_instance::{get_mmc_instance, MmcInstance},
+        mod_loader::{self, ModLoader},
+        mod_metadata::{ModMetadata, ModSide},
+        project::{Launcher, Project},
+        versions::{LoaderVersion, MinecraftVersion},
+    },
+    utils::{
+        compare::{compare, Side},
+        patterns::{is_pattern, Patterns},
+    },
 };
-use anyhow::{Context, Result};
-use once_cell::sync::Lazy;
-use semver::Version;
+use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
+use semver::{Identifier, Version};
 use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Borrow,
     cmp::Ordering,
-    collections::BTreeSet,
-    fs::File,
+    collections::{BTreeMap, BTreeSet},
+    fs::{copy, rename, File},
     io::{BufReader, BufWriter, Read, Write},
     path::{Path, PathBuf},
 };
+use yaml_rust::{yaml::Hash, Yaml};
 
-pub static MANIFEST_FILE: Lazy<PathBuf> = Lazy::new(|| PathBuf::from(".manifest.yaml"));
+pub const MANIFEST_FILE: &str = ".manifest.yaml";
+
+/// Names the manifest is looked for by in the pack directory.
+///
+/// The `mcpacker` ones keep the files their mods resolved to in `mcpacker.lock`.
+pub const MANIFEST_FILES: [&str; 6] = [
+    MANIFEST_FILE,
+    ".manifest.toml",
+    ".manifest.json",
+    "mcpacker.yaml",
+    "mcpacker.toml",
+    "mcpacker.json",
+];
+
+/// Version of the manifest format written by this version of mcpacker.
+///
+/// Manifests without a `schemaVersion` are version 1.
+pub const SCHEMA_VERSION: u32 = 2;
+
+// MIGRATIONS[i] upgrades a manifest from schema version i + 1 to i + 2.
+const MIGRATIONS: [fn(&mut Hash) -> Result<()>; (SCHEMA_VERSION - 1) as usize] = [migrate_v1_to_v2];
+
+/// Upgrades a manifest document to `SCHEMA_VERSION` one version at a time.
+///
+/// Returns the schema version the document was written with. The document is
+/// a yaml-rust tree since it keeps scalars like `1.20` as written, other formats
+/// are converted to it.
+pub fn migrate(doc: &mut Yaml) -> Result<u32> {
+    let map = match doc {
+        Yaml::Hash(map) => map,
+        _ => return Err(anyhow!("manifest is not a mapping of fields")),
+    };
+    let key = Yaml::String("schemaVersion".to_string());
+    let from = match map.get(&key) {
+        None => 1,
+        Some(v) => v
+            .as_i64()
+            .filter(|v| *v >= 1 && *v <= i64::from(u32::MAX))
+            .ok_or_else(|| anyhow!("schemaVersion needs to be a whole number from 1"))?
+            as u32,
+    };
+    if from > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "manifest has schema version {} but this version of mcpacker only supports up to {}, update mcpacker to use it",
+            from,
+            SCHEMA_VERSION
+        ));
+    }
+    for (i, step) in MIGRATIONS.iter().enumerate().skip(from as usize - 1) {
+        step(map).with_context(|| {
+            format!(
+                "could not migrate manifest from schema version {} to {}",
+                i + 1,
+                i + 2
+            )
+        })?;
+    }
+    let _ = map.insert(key, Yaml::Integer(i64::from(SCHEMA_VERSION)));
+    Ok(from)
+}
+
+// Version 1 allowed an empty modLoader and any casing of its name.
+fn migrate_v1_to_v2(map: &mut Hash) -> Result<()> {
+    let key = Yaml::String("modLoader".to_string());
+    let loader = match map.get(&key) {
+        Some(Yaml::String(s)) => s.trim().to_lowercase(),
+        Some(Yaml::Null) | None => return Ok(()),
+        Some(v) => return Err(anyhow!("modLoader {:?} is not a string", v)),
+    };
+    if loader.is_empty() {
+        let _ = map.remove(&key);
+    } else {
+        let _ = map.insert(key, Yaml::String(loader));
+    }
+    Ok(())
+}
 
 #[cfg(target_os = "windows")]
-fn clean_path(p: impl AsRef<Path>) -> PathBuf {
-    PathBuf::from(p.as_ref().to_string_lossy().replace("\\", "/"))
+pub fn clean_path(p: impl AsRef<Path>) -> String {
+    p.as_ref().to_string_lossy().replace("\\", "/")
 }
 
 #[cfg(not(target_os = "windows"))]
-fn clean_path(p: impl AsRef<Path>) -> PathBuf {}
+pub fn clean_path(p: impl AsRef<Path>) -> String {
+    p.as_ref().to_string_lossy().into()
+}
 
-pub fn get_manifest() -> Result<Manifest> {
-    Manifest::from_reader(BufReader::new(
-        File::open(Lazy::force(&MANIFEST_FILE)).with_context(|| {
-            format!(
-                "could not open {} for reading",
-                MANIFEST_FILE.to_string_lossy()
-            )
-        })?,
-    ))
+fn set_insert<T: Ord>(set: &mut Option<BTreeSet<T>>, value: T) -> bool {
+    set.get_or_insert_with(BTreeSet::new).insert(value)
+}
+
+fn set_remove<T, Q>(set: &mut Option<BTreeSet<Q>>, value: &T) -> bool
+where
+    T: Ord + ?Sized,
+    Q: Ord + Borrow<T>,
+{
+    let removed = match set {
+        Some(s) => s.remove(value),
+        None => false,
+    };
+    if matches!(set, Some(s) if s.is_empty()) {
+        *set = None;
+    }
+    removed
+}
+
+/// Reads the manifest and every manifest it extends.
+pub fn get_manifest(project: &Project) -> Result<Manifest> {
+    load_manifest(project.manifest_file(), project.root(), &mut Vec::new())
+}
+
+/// Reads the instance managed by `launcher` into a manifest.
+pub fn get_instance_manifest(project: &Project, launcher: Launcher) -> Result<Manifest> {
+    Ok(match launcher {
+        Launcher::CurseForge => (&get_minecraft_instance(project)?).into(),
+        Launcher::Prism => (&get_mmc_instance(project)?).into(),
+    })
 }
 
-pub fn create_manifest_file() -> Result<BufWriter<File>> {
-    Ok(BufWriter::new(
-        File::create(Lazy::force(&MANIFEST_FILE)).with_context(|| {
+fn load_manifest(path: &Path, dir: &Path, chain: &mut Vec<PathBuf>) -> Result<Manifest> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("could not open {} for reading", path.to_string_lossy()))?;
+    if chain.contains(&canonical) {
+        return Err(anyhow!(
+            "{} extends itself through {}",
+            canonical.to_string_lossy(),
+            chain[0].to_string_lossy()
+        ));
+    }
+    chain.push(canonical);
+    let (mut manifest, _) = read_manifest_file(path)?;
+    manifest.dir = dir.to_path_buf();
+    if let Some(extends) = &manifest.extends {
+        let base_path = path
+            .parent()
+            .map(|p| p.join(extends))
+            .unwrap_or_else(|| extends.clone());
+        let base_dir = base_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        manifest.base = Some(Box::new(load_manifest(&base_path, &base_dir, chain)?));
+    }
+    Ok(manifest)
+}
+
+/// Reads a single manifest file, also returning the schema version it had.
+///
+/// The mods of `mcpacker` manifests are completed from the lock file next to them.
+pub fn read_manifest_file(path: &Path) -> Result<(Manifest, u32)> {
+    let lock = if is_split(path) {
+        Some(Lock::read(&lock_path(path))?)
+    } else {
+        None
+    };
+    Manifest::from_reader_locked(
+        BufReader::new(
+            File::open(path).with_context(|| {
+                format!("could not open {} for reading", path.to_string_lossy())
+            })?,
+        ),
+        ManifestFormat::from_path(path)?,
+        lock.as_ref(),
+    )
+    .with_context(|| format!("could not read {}", path.to_string_lossy()))
+}
+
+/// Writes the manifest to disk without ever leaving a partially written file behind.
+///
+/// The manifest is serialized to a temporary file first and then renamed over the
+/// existing one. The previous version is kept as a backup so it can be restored with `undo`.
+///
+/// `mcpacker` manifests only keep what was written by hand, the files their mods
+/// resolved to are written to the lock file next to them.
+pub fn write_manifest(project: &Project, manifest: &Manifest) -> Result<()> {
+    let path = project.manifest_file();
+    let format = ManifestFormat::from_path(path)?;
+    if !is_split(path) {
+        return write_with_backup(
+            path,
+            &project.manifest_backup_file(),
+            &project.manifest_tmp_file(),
+            |w| manifest.to_writer(w, format),
+        );
+    }
+    let mut doc = ManifestFormat::Yaml.to_yaml(
+        &ManifestFormat::Yaml
+            .serialize(manifest)
+            .with_context(|| "could not serialize from Manifest")?,
+    )?;
+    let lock = Lock::split(&mut doc);
+    // The lock goes first since extra mods in it are ignored but missing ones are not
+    write_lock_file(project, &lock)?;
+    let s = format
+        .serialize(&YamlTree(&doc))
+        .with_context(|| "could not serialize from Manifest")?;
+    write_with_backup(
+        path,
+        &project.manifest_backup_file(),
+        &project.manifest_tmp_file(),
+        |w| {
+            w.write_all(s.as_bytes())
+                .with_context(|| "could not write Manifest")
+        },
+    )
+}
+
+/// Writes the lock file of an `mcpacker` manifest, keeping a backup like `write_manifest`.
+///
+/// The manifest is backed up as it is, so `undo` only takes back the lock.
+pub fn write_lock(project: &Project, lock: &Lock) -> Result<()> {
+    let path = project.manifest_file();
+    let backup = project.manifest_backup_file();
+    copy_file(path, &backup)?;
+    write_lock_file(project, lock)
+}
+
+fn write_lock_file(project: &Project, lock: &Lock) -> Result<()> {
+    let s = lock.serialize()?;
+    write_with_backup(
+        &project.lock_file(),
+        &project.lock_backup_file(),
+        &project.lock_tmp_file(),
+        |w| {
+            w.write_all(s.as_bytes())
+                .with_context(|| "could not write lock file")
+        },
+    )
+}
+
+fn write_with_backup<F>(path: &Path, backup: &Path, tmp: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    let mut writer = BufWriter::new(
+        File::create(tmp)
+            .with_context(|| format!("could not open {} for writing", tmp.to_string_lossy()))?,
+    );
+    write(&mut writer)?;
+    let file = writer
+        .into_inner()
+        .with_context(|| format!("could not flush {}", tmp.to_string_lossy()))?;
+    file.sync_all()
+        .with_context(|| format!("could not sync {} to disk", tmp.to_string_lossy()))?;
+    if path.exists() {
+        copy_file(path, backup)?;
+    }
+    rename(tmp, path).with_context(|| {
+        format!(
+            "could not move {} to {}",
+            tmp.to_string_lossy(),
+            path.to_string_lossy()
+        )
+    })
+}
+
+// Only whether the copy worked matters, not how many bytes it took.
+fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    copy(from, to).map(drop).with_context(|| {
+        format!(
+            "could not copy {} to {}",
+            from.to_string_lossy(),
+            to.to_string_lossy()
+        )
+    })
+}
+
+/// Swaps the manifest with its backup.
+///
+/// The current manifest becomes the new backup so running this twice is a redo.
+/// The lock file of an `mcpacker` manifest is swapped along with it.
+pub fn restore_manifest_backup(project: &Project) -> Result<()> {
+    let backup = project.manifest_backup_file();
+    if !backup.exists() {
+        return Err(anyhow!(
+            "{} does not exist, nothing to undo",
+            backup.to_string_lossy()
+        ));
+    }
+    swap_with_backup(
+        project.manifest_file(),
+        &backup,
+        &project.manifest_tmp_file(),
+    )?;
+    let lock_backup = project.lock_backup_file();
+    if is_split(project.manifest_file()) && lock_backup.exists() {
+        swap_with_backup(&project.lock_file(), &lock_backup, &project.lock_tmp_file())?;
+    }
+    Ok(())
+}
+
+fn swap_with_backup(path: &Path, backup: &Path, tmp: &Path) -> Result<()> {
+    let current = path.exists();
+    if current {
+        copy_file(path, tmp)?;
+    }
+    rename(backup, path).with_context(|| {
+        format!(
+            "could not move {} to {}",
+            backup.to_string_lossy(),
+            path.to_string_lossy()
+        )
+    })?;
+    if current {
+        rename(tmp, backup).with_context(|| {
             format!(
-                "could not open {} for writing",
-                MANIFEST_FILE.to_string_lossy()
+                "could not move {} to {}",
+                tmp.to_string_lossy(),
+                backup.to_string_lossy()
             )
-        })?,
-    ))
+        })?;
+    }
+    Ok(())
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The `.manifest.yaml` of a mod pack.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Manifest {
+    /// Version of the manifest format, 1 if it is missing.
+    #[serde(default = "current_schema_version")]
+    schema_version: u32,
+    /// Name of the mod pack.
     pub name: String,
+    /// Semver version of the mod pack.
+    #[schemars(with = "String")]
     pub version: Version,
     pub author: String,
-    pub minecraft_version: Version,
-    pub mod_loader: String,
-    pub mod_loader_version: Version,
-    includes: Option<BTreeSet<PathBuf>>, // Can include a jar file not in mod list
+    pub minecraft_version: MinecraftVersion,
+    #[serde(
+        default,
+        deserialize_with = "mod_loader::deserialize_optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mod_loader: Option<ModLoader>,
+    pub mod_loader_version: LoaderVersion,
+    /// Manifest this one is derived from, relative to this manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extends: Option<PathBuf>,
+    /// Project IDs of mods from the extended manifest to leave out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exclude_mods: Option<BTreeSet<u32>>,
+    /// Paths or glob patterns that are never packed, even from the extended manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exclude_includes: Option<BTreeSet<String>>,
+    /// Paths or glob patterns of files to pack, which can include jars that are not in mods.
+    includes: Option<BTreeSet<String>>,
+    /// Mods installed from CurseForge.
     mods: Option<BTreeSet<Mod>>,
+    /// Variants of the pack that can be packed with --profile.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profiles: Option<BTreeMap<String, Profile>>,
+    #[serde(skip)]
+    base: Option<Box<Manifest>>,
+    /// Directory the includes are relative to.
+    #[serde(skip)]
+    dir: PathBuf,
 }
 
 impl Manifest {
-    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
-        serde_yaml::from_reader(reader).with_context(|| "could not deserialize into Manifest")
+    /// Reads a manifest of any supported schema version, also returning the version it had.
+    ///
+    /// With a lock the mods only need their project ID, the rest is taken from the lock.
+    pub fn from_reader_locked<R: Read>(
+        mut reader: R,
+        format: ManifestFormat,
+        lock: Option<&Lock>,
+    ) -> Result<(Self, u32)> {
+        let mut s = String::new();
+        let _ = reader
+            .read_to_string(&mut s)
+            .with_context(|| "could not read Manifest")?;
+        let mut doc = format
+            .to_yaml(&s)
+            .with_context(|| "could not deserialize into Manifest")?;
+        let from = migrate(&mut doc)?;
+        if let Some(lock) = lock {
+            let missing = lock.join(&mut doc);
+            if !missing.is_empty() {
+                return Err(anyhow!(
+                    "mods {} are not in the lock file yet, run update to resolve them",
+                    missing
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+        // Manifests that did not change are read as written to keep line numbers in errors
+        let manifest = if from == SCHEMA_VERSION && lock.is_none() {
+            format.deserialize(&s)
+        } else {
+            from_yaml(&doc)
+        }
+        .with_context(|| "could not deserialize into Manifest")?;
+        Ok((manifest, from))
     }
 
-    pub fn to_writer<W: Write>(&self, writer: W) -> Result<()> {
-        serde_yaml::to_writer(writer, &self)
-            .with_context(|| "could not serialize from MinecraftInstance")
+    pub fn to_writer<W: Write>(&self, mut writer: W, format: ManifestFormat) -> Result<()> {
+        let s = format
+            .serialize(self)
+            .with_context(|| "could not serialize from Manifest")?;
+        writer
+            .write_all(s.as_bytes())
+            .with_context(|| "could not write Manifest")
     }
 
     pub fn get_mods(&self) -> Option<&BTreeSet<Mod>> {
         self.mods.as_ref().map(|mods| mods)
     }
 
+    /// Mods of this manifest merged with the ones it extends.
+    ///
+    /// A project listed here replaces the file the extended manifest has for it.
+    pub fn effective_mods(&self) -> BTreeSet<Mod> {
+        let mut mods = match &self.base {
+            Some(base) => base.effective_mods(),
+            None => BTreeSet::new(),
+        };
+        if let Some(exclude) = &self.exclude_mods {
+            mods.retain(|m| !exclude.contains(&m.project_id));
+        }
+        if let Some(local) = &self.mods {
+            let local_ids: BTreeSet<u32> = local.iter().map(|m| m.project_id).collect();
+            mods.retain(|m| !local_ids.contains(&m.project_id));
+            for m in local {
+                let _ = mods.insert(m.clone());
+            }
+        }
+        mods
+    }
+
+    /// This manifest followed by every manifest it extends.
+    pub fn layers(&self) -> Vec<&Manifest> {
+        let mut layers = vec![self];
+        let mut cur = self;
+        while let Some(base) = &cur.base {
+            layers.push(base);
+            cur = base;
+        }
+        layers
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Builds the variant of the pack described by the profile `name`.
+    ///
+    /// The variant extends this manifest with the changes from the profile,
+    /// and gets the profile appended to its name and version build metadata.
+    pub fn with_profile(&self, name: &str) -> Result<Manifest> {
+        let profile = self
+            .layers()
+            .into_iter()
+            .find_map(|l| l.profiles.as_ref().and_then(|p| p.get(name)))
+            .ok_or_else(|| anyhow!("profile {} does not exist", name))?;
+        let mut version = self.version.clone();
+        version
+            .build
+            .push(Identifier::AlphaNumeric(name.to_string()));
+        Ok(Manifest {
+            schema_version: SCHEMA_VERSION,
+            name: format!("{}-{}", self.name, name),
+            version,
+            author: self.author.clone(),
+            minecraft_version: self.minecraft_version.clone(),
+            mod_loader: self.mod_loader,
+            mod_loader_version: self.mod_loader_version.clone(),
+            extends: None,
+            exclude_mods: profile.exclude_mods.clone(),
+            exclude_includes: profile.exclude_includes.clone(),
+            includes: profile.includes.clone(),
+            mods: profile.mods.clone(),
+            profiles: None,
+            base: Some(Box::new(self.clone())),
+            dir: self.dir.clone(),
+        })
+    }
+
+    /// Builds the variant of the pack for one side, leaving out the mods only needed on the other.
+    pub fn for_side(&self, side: ModSide) -> Manifest {
+        let exclude_mods: BTreeSet<u32> = self
+            .effective_mods()
+            .iter()
+            .filter(|m| matches!(m.side(), Some(s) if s != side && s != ModSide::Both))
+            .map(|m| m.project_id)
+            .collect();
+        Manifest {
+            extends: None,
+            exclude_mods: Some(exclude_mods),
+            exclude_includes: None,
+            includes: None,
+            mods: None,
+            profiles: None,
+            base: Some(Box::new(self.clone())),
+            ..self.clone()
+        }
+    }
+
+    pub fn add_exclude_mod(&mut self, project_id: u32) -> bool {
+        set_insert(&mut self.exclude_mods, project_id)
+    }
+
+    pub fn remove_exclude_mod(&mut self, project_id: u32) -> bool {
+        set_remove(&mut self.exclude_mods, &project_id)
+    }
+
     pub fn add_mod(&mut self, module: Mod) -> bool {
         match &mut self.mods {
             Some(m) => m.insert(module),
@@ -85,6 +551,38 @@ impl Manifest {
         }
     }
 
+    /// Replaces the mod of this manifest with the same project and file.
+    pub fn update_mod(&mut self, module: Mod) -> bool {
+        match &mut self.mods {
+            Some(m) => m.replace(module).is_some(),
+            None => false,
+        }
+    }
+
+    /// Mods of this manifest, not the ones it extends, not looked up on CurseForge yet.
+    pub fn mods_without_info(&self) -> Vec<Mod> {
+        self.mods
+            .iter()
+            .flatten()
+            .filter(|m| m.slug.is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Mods of this manifest, not the ones it extends, whose jar has not been read yet.
+    ///
+    /// Inherited mods are written to the extended manifest, so they get their
+    /// metadata from running `sync` on that pack. Until then `check` and the
+    /// duplicate mod ID check read their jars from the mods folder instead.
+    pub fn mods_without_metadata(&self) -> Vec<Mod> {
+        self.mods
+            .iter()
+            .flatten()
+            .filter(|m| m.metadata.is_none())
+            .cloned()
+            .collect()
+    }
+
     pub fn remove_mod(&mut self, module: &Mod) -> bool {
         match &mut self.mods {
             Some(m) => {
@@ -101,69 +599,37 @@ impl Manifest {
         }
     }
 
-    pub fn get_mod_by_filename(&self, path: impl AsRef<str>) -> Option<&Mod> {
-        match &self.mods {
-            None => None,
-            Some(mods) => {
-                for m in mods {
-                    if m.file_name == path.as_ref() {
-                        return Some(m);
-                    }
-                }
-                None
-            }
-        }
-    }
-
-    fn set_mods(&mut self, modules: BTreeSet<Mod>) {
-        self.mods = Some(modules);
-    }
-
-    fn clear_mods(&mut self) {
-        self.mods = None;
+    /// Effective mods by the name of their jar, to look up many jars at once.
+    pub fn mods_by_filename(&self) -> BTreeMap<String, Mod> {
+        self.effective_mods()
+            .into_iter()
+            .map(|m| (m.file_name.clone(), m))
+            .collect()
     }
 
-    pub fn get_includes(&self) -> Option<&BTreeSet<PathBuf>> {
-        self.includes.as_ref().map(|includes| includes)
+    /// Adds a path or glob pattern, relative to the pack, to pack as an override.
+    pub fn add_include(&mut self, include: String) -> bool {
+        set_insert(&mut self.includes, include)
     }
 
-    pub fn add_include(&mut self, mut include: PathBuf) -> bool {
-        include = clean_path(&include);
-        match &mut self.includes {
-            Some(i) => i.insert(include),
-            None => {
-                let mut includes = BTreeSet::new();
-                let _ = includes.insert(include);
-                self.includes = Some(includes);
-                true
-            }
-        }
-    }
-
-    pub fn include_exists(&self, include: impl AsRef<Path>) -> bool {
-        let include = clean_path(include);
-        match self.includes.as_ref() {
-            None => false,
-            Some(i) => i.contains(&include),
-        }
-    }
-
-    pub fn include_contained(&self, include: impl AsRef<Path>) -> Option<PathBuf> {
-        let mut ppath = clean_path(include);
-        while let Some(p) = ppath.parent() {
-            if self.include_exists(p) {
-                return Some(p.to_path_buf());
-            }
-            ppath = p.to_owned();
-        }
-        None
+    /// Another include that already packs `include`.
+    pub fn include_contained(&self, include: &str) -> Result<Option<String>> {
+        let others = match &self.includes {
+            Some(i) => i.iter().filter(|i| *i != include).collect(),
+            None => Vec::new(),
+        };
+        let patterns = Patterns::new(&self.dir, others)?;
+        let is_dir = self.dir.join(include).is_dir();
+        Ok(patterns
+            .matched(Path::new(include), is_dir)
+            .map(str::to_string))
     }
 
-    pub fn includes_clean(&mut self) {
+    pub fn includes_clean(&mut self) -> Result<()> {
         if let Some(includes) = &self.includes {
             let mut remove = Vec::new();
             for include in includes {
-                if self.include_contained(include).is_some() {
+                if !is_pattern(include) && self.include_contained(include)?.is_some() {
                     remove.push(include.clone());
                 }
             }
@@ -171,57 +637,88 @@ impl Manifest {
                 let _ = self.remove_include(&r);
             }
         }
+        Ok(())
     }
 
-    pub fn remove_include(&mut self, include: impl AsRef<Path>) -> bool {
-        let include = clean_path(include);
-        match &mut self.includes {
-            Some(i) => {
-                if i.remove(&include) {
-                    if i.is_empty() {
-                        self.includes = None;
-                    }
-                    true
-                } else {
-                    false
-                }
+    pub fn remove_include(&mut self, include: &str) -> bool {
+        set_remove(&mut self.includes, include)
+    }
+
+    /// Adds a path or glob pattern of files that should never be packed.
+    ///
+    /// This also applies to the includes of any manifest this one extends.
+    pub fn add_exclude_include(&mut self, exclude: String) -> bool {
+        set_insert(&mut self.exclude_includes, exclude)
+    }
+
+    pub fn remove_exclude_include(&mut self, exclude: &str) -> bool {
+        set_remove(&mut self.exclude_includes, exclude)
+    }
+
+    pub fn include_patterns(&self) -> Result<Patterns> {
+        Patterns::new(&self.dir, self.includes.iter().flatten())
+    }
+
+    pub fn exclude_patterns(&self) -> Result<Patterns> {
+        Patterns::new(&self.dir, self.exclude_includes.iter().flatten())
+    }
+
+    /// Whether `p` is packed by this manifest or any manifest it extends.
+    ///
+    /// `p` is relative to the pack.
+    pub fn is_included(&self, p: &Path, is_dir: bool) -> Result<bool> {
+        let mut excludes = Vec::new();
+        for layer in self.layers() {
+            excludes.push(layer.exclude_patterns()?);
+            if excludes.iter().any(|e| e.matched(p, is_dir).is_some()) {
+                return Ok(false);
+            }
+            if layer.include_patterns()?.matched(p, is_dir).is_some() {
+                return Ok(true);
             }
-            None => false,
         }
+        Ok(false)
     }
 
+    /// Brings the mods in line with `new` and returns the mods (added, removed).
+    ///
+    /// Only the local delta is changed. Mods that come from an extended manifest
+    /// are excluded instead of removed, and added back by dropping the exclude.
     pub fn sync_mods(&mut self, new: &Manifest) -> (Option<Vec<Mod>>, Option<Vec<Mod>>) {
         let mut rm = Vec::new();
         let mut add = Vec::new();
-        match (self.get_mods(), new.get_mods()) {
-            (Some(m), Some(n)) => {
-                let comp = compare(m.iter(), n.iter());
-                for c in comp {
-                    match c {
-                        Side::Left(m) => rm.push(m.clone()),
-                        Side::Right(m) => add.push(m.clone()),
-                    }
-                }
-            }
-            (None, Some(n)) => {
-                for new in n {
-                    add.push(new.clone());
-                }
-                self.set_mods(n.clone());
+        let current = self.effective_mods();
+        let empty = BTreeSet::new();
+        let comp = compare(current.iter(), new.get_mods().unwrap_or(&empty).iter());
+        for c in comp {
+            match c {
+                Side::Left(m) => rm.push(m.clone()),
+                Side::Right(m) => add.push(m.clone()),
             }
-            (Some(m), None) => {
-                for old in m {
-                    rm.push(old.clone());
-                }
-                self.clear_mods();
+        }
+        // A project that changed file keeps what is known about it
+        for m in &mut add {
+            if let Some(old) = rm.iter().find(|r| r.project_id == m.project_id) {
+                m.fill_info(old);
             }
-            (None, None) => {}
         }
+        let inherited = match &self.base {
+            Some(base) => base.effective_mods(),
+            None => BTreeSet::new(),
+        };
         for m in &rm {
-            let _ = self.remove_mod(&m);
+            // An inherited project that moved to another file is replaced, not excluded
+            let replaced = add.iter().any(|a| a.project_id == m.project_id);
+            if !self.remove_mod(m) && !replaced {
+                let _ = self.add_exclude_mod(m.project_id);
+            }
         }
         for m in &add {
-            let _ = self.add_mod(m.clone());
+            if inherited.contains(m) {
+                let _ = self.remove_exclude_mod(m.project_id);
+            } else {
+                let _ = self.add_mod(m.clone());
+            }
         }
         match (add.len(), rm.len()) {
             (0, 0) => (None, None),
@@ -234,26 +731,39 @@ impl Manifest {
 
 impl From<&MinecraftInstance> for Manifest {
     fn from(mi: &MinecraftInstance) -> Self {
-        let version = if mi.manifest.is_some() {
-            mi.manifest.as_ref().unwrap().version.clone()
-        } else {
-            mi.game_version.clone()
+        let version = match mi.manifest.as_ref().map(ManifestJson::pack_version) {
+            Some(Ok(version)) => version,
+            Some(Err(e)) => {
+                println!(
+                    "warning: {}, starting from the Minecraft version instead",
+                    e
+                );
+                pack_version(&mi.game_version)
+            }
+            None => pack_version(&mi.game_version),
         };
         let m = mi.base_mod_loader.get_mod_loader();
         let (mod_loader, mod_loader_version) = if let Some(loader) = m {
-            (loader.0.to_string(), loader.1)
+            (Some(loader.0), loader.1)
         } else {
-            (String::new(), Version::new(0, 0, 0))
+            (None, LoaderVersion::default())
         };
         let mut m = Manifest {
+            schema_version: SCHEMA_VERSION,
             name: mi.name.clone(),
             version,
             author: mi.custom_author.clone(),
             minecraft_version: mi.game_version.clone(),
             mod_loader,
             mod_loader_version,
+            extends: None,
+            exclude_mods: None,
+            exclude_includes: None,
             includes: None,
             mods: None,
+            profiles: None,
+            base: None,
+            dir: PathBuf::new(),
         };
         if let Some(addons) = mi.installed_addons.as_ref() {
             for addon in addons {
@@ -264,46 +774,164 @@ impl From<&MinecraftInstance> for Manifest {
     }
 }
 
+impl From<&MmcInstance> for Manifest {
+    fn from(mi: &MmcInstance) -> Self {
+        let minecraft_version = mi.pack.minecraft_version().unwrap_or_default();
+        let (mod_loader, mod_loader_version) = match mi.pack.get_mod_loader() {
+            Some((loader, version)) => (Some(loader), version),
+            None => (None, LoaderVersion::default()),
+        };
+        let mut m = Manifest {
+            name: mi.name.clone(),
+            version: pack_version(&minecraft_version),
+            minecraft_version,
+            mod_loader,
+            mod_loader_version,
+            ..Manifest::default()
+        };
+        for addon in &mi.installed_addons {
+            let _ = m.add_mod(addon.into());
+        }
+        m
+    }
+}
+
+// New packs start at the Minecraft version when it is a valid pack version.
+fn pack_version(minecraft_version: &MinecraftVersion) -> Version {
+    minecraft_version
+        .to_semver()
+        .unwrap_or_else(|| Version::new(0, 0, 0))
+}
+
+fn current_schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
 impl Default for Manifest {
     fn default() -> Self {
         Manifest {
+            schema_version: SCHEMA_VERSION,
             name: String::new(),
             version: Version::new(0, 0, 0),
             author: String::new(),
-            minecraft_version: Version::new(0, 0, 0),
-            mod_loader: String::new(),
-            mod_loader_version: Version::new(0, 0, 0),
+            minecraft_version: MinecraftVersion::default(),
+            mod_loader: None,
+            mod_loader_version: LoaderVersion::default(),
+            extends: None,
+            exclude_mods: None,
+            exclude_includes: None,
             includes: None,
             mods: None,
+            profiles: None,
+            base: None,
+            dir: PathBuf::new(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Named variant of a pack that adds or removes mods and includes.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// Project IDs of mods to leave out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exclude_mods: Option<BTreeSet<u32>>,
+    /// Includes to leave out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exclude_includes: Option<BTreeSet<String>>,
+    /// Includes to add.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    includes: Option<BTreeSet<String>>,
+    /// Mods to add.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mods: Option<BTreeSet<Mod>>,
+}
+
+/// A mod file from CurseForge.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Mod {
+    /// CurseForge project ID of the mod.
     #[serde(rename = "projectID")]
     pub project_id: u32,
+    /// Name of the project, filled in by sync.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Short name of the project in its CurseForge url.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    /// Names of the authors of the project on CurseForge.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authors: Option<Vec<String>>,
+    /// Page of the project on CurseForge.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Side the mod is needed on, when the jar does not say or says it wrong.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub side: Option<ModSide>,
+    /// CurseForge ID of the file of the mod.
     #[serde(rename = "fileID")]
     pub file_id: u32,
-    // Used for verifying the file downloaded
+    /// Name of the jar in the mods folder.
     pub file_name: String,
+    /// CurseForge murmur2 fingerprint of the jar, used for verifying the file downloaded.
     pub fingerprint: u32,
+    /// Size of the jar in bytes, used for verifying the file downloaded.
     pub file_size: u64,
+    /// Mods declared by the jar, read from it during sync.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Vec<ModMetadata>>,
 }
 
 impl From<&InstalledAddon> for Mod {
     fn from(ia: &InstalledAddon) -> Self {
         Mod {
             project_id: ia.addon_id,
+            name: None,
+            slug: None,
+            authors: None,
+            url: None,
+            side: None,
             file_id: ia.installed_file.id,
             file_name: ia.installed_file.file_name.to_string(),
             file_size: ia.installed_file.file_length,
             fingerprint: ia.installed_file.package_fingerprint,
+            metadata: None,
+        }
+    }
+}
+
+impl Mod {
+    /// Takes the name, slug, authors, url and side of `other` where this mod has none.
+    pub fn fill_info(&mut self, other: &Mod) {
+        fn fill<T: Clone>(field: &mut Option<T>, other: &Option<T>) {
+            if field.is_none() {
+                *field = other.clone();
+            }
+        }
+        fill(&mut self.name, &other.name);
+        fill(&mut self.slug, &other.slug);
+        fill(&mut self.authors, &other.authors);
+        fill(&mut self.url, &other.url);
+        fill(&mut self.side, &other.side);
+    }
+
+    /// Side the mod is needed on, as set in the manifest or else as declared by the jar.
+    pub fn side(&self) -> Option<ModSide> {
+        if self.side.is_some() {
+            return self.side;
+        }
+        let mut sides = self.metadata.iter().flatten().map(|m| m.side);
+        let first = sides.next()??;
+        if sides.all(|s| s == Some(first)) {
+            Some(first)
+        } else {
+            None
         }
     }
 }
 
+// Only the project and file matter, the rest describes them.
 impl PartialEq for Mod {
     fn eq(&self, other: &Self) -> bool {
         self.project_id == other.project_id && self.file_id == other.file_id
@@ -326,3 +954,160 @@ impl PartialOrd for Mod {
         Some(self.cmp(other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_dir::TestDir;
+
+    fn text(mods: &str) -> String {
+        format!(
+            "name: t\nversion: 1.0.0\nauthor: t\nminecraftVersion: 1.20.1\nmodLoaderVersion: 47.2.0\nmods:\n{}",
+            mods
+        )
+    }
+
+    fn manifest(mods: &str) -> Manifest {
+        serde_yaml::from_str(&text(mods)).unwrap()
+    }
+
+    #[test]
+    fn effective_mods_replace_the_file_of_the_base() {
+        let mut derived = manifest(
+            "  - {projectID: 1, fileID: 11, fileName: a-2.jar, fingerprint: 0, fileSize: 0}\n",
+        );
+        derived.base = Some(Box::new(manifest(
+            "  - {projectID: 1, fileID: 10, fileName: a-1.jar, fingerprint: 0, fileSize: 0}\n  - {projectID: 2, fileID: 20, fileName: b.jar, fingerprint: 0, fileSize: 0}\n",
+        )));
+        let files: Vec<u32> = derived.effective_mods().iter().map(|m| m.file_id).collect();
+        assert_eq!(files, vec![11, 20]);
+        assert!(!derived.mods_by_filename().contains_key("a-1.jar"));
+
+        // Syncing an inherited project to a new file adds it locally without excluding it
+        let base = derived.base.take().unwrap();
+        let mut derived = manifest("");
+        derived.base = Some(base);
+        let _ = derived.sync_mods(&manifest(
+            "  - {projectID: 1, fileID: 11, fileName: a-2.jar, fingerprint: 0, fileSize: 0}\n  - {projectID: 2, fileID: 20, fileName: b.jar, fingerprint: 0, fileSize: 0}\n",
+        ));
+        assert_eq!(derived.exclude_mods, None);
+        let files: Vec<u32> = derived.effective_mods().iter().map(|m| m.file_id).collect();
+        assert_eq!(files, vec![11, 20]);
+        let local: Vec<u32> = derived
+            .get_mods()
+            .unwrap()
+            .iter()
+            .map(|m| m.file_id)
+            .collect();
+        assert_eq!(local, vec![11]);
+    }
+
+    fn doc(text: &str) -> Yaml {
+        ManifestFormat::Yaml.to_yaml(text).unwrap()
+    }
+
+    #[test]
+    fn migrate_v1_manifest() {
+        let mut v1 = doc("name: t\nminecraftVersion: 1.20\nmodLoader: Forge \n");
+        assert_eq!(migrate(&mut v1).unwrap(), 1);
+        assert_eq!(v1["modLoader"].as_str(), Some("forge"));
+        assert_eq!(
+            v1["schemaVersion"].as_i64(),
+            Some(i64::from(SCHEMA_VERSION))
+        );
+        assert_eq!(v1["minecraftVersion"], Yaml::Real("1.20".to_string()));
+        let mut empty_loader = doc("name: t\nmodLoader: \"\"\n");
+        assert_eq!(migrate(&mut empty_loader).unwrap(), 1);
+        assert!(empty_loader["modLoader"].is_badvalue());
+    }
+
+    #[test]
+    fn migrate_current_manifest() {
+        let mut current = doc(&format!(
+            "schemaVersion: {}\nmodLoader: forge\n",
+            SCHEMA_VERSION
+        ));
+        assert_eq!(migrate(&mut current).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn migrate_newer_manifest() {
+        let mut newer = doc(&format!("schemaVersion: {}\n", SCHEMA_VERSION + 1));
+        let e = migrate(&mut newer).unwrap_err().to_string();
+        assert!(e.contains("update mcpacker"), "{}", e);
+    }
+
+    #[test]
+    fn migrate_invalid_schema_version() {
+        for version in &["two", "1.5", "0", "-1"] {
+            let mut invalid = doc(&format!("schemaVersion: {}\n", version));
+            let e = migrate(&mut invalid).unwrap_err().to_string();
+            assert!(e.contains("whole number"), "{}: {}", version, e);
+        }
+    }
+
+    fn project(dir: &TestDir, file: &str, text: &str) -> Project {
+        let _ = dir.write(file, text);
+        Project::new(Some(dir.path()), Some(Path::new(file))).unwrap()
+    }
+
+    fn name(project: &Project) -> String {
+        get_manifest(project).unwrap().name
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let dir = TestDir::new("undo");
+        let project = project(&dir, MANIFEST_FILE, &text(""));
+        let mut m = get_manifest(&project).unwrap();
+        for name in &["two", "three"] {
+            m.name = name.to_string();
+            write_manifest(&project, &m).unwrap();
+        }
+        restore_manifest_backup(&project).unwrap();
+        assert_eq!(name(&project), "two");
+        restore_manifest_backup(&project).unwrap();
+        assert_eq!(name(&project), "three");
+        restore_manifest_backup(&project).unwrap();
+        assert_eq!(name(&project), "two");
+    }
+
+    #[test]
+    fn undo_swaps_the_lock() {
+        let dir = TestDir::new("undo-lock");
+        let a1 = "  - {projectID: 1, fileID: 10, fileName: a-1.jar, fingerprint: 0, fileSize: 0}\n";
+        let a2 = "  - {projectID: 1, fileID: 11, fileName: a-2.jar, fingerprint: 0, fileSize: 0}\n";
+        let project = project(&dir, "mcpacker.yaml", &text(a1));
+        let file_ids = |p: &Project| -> Vec<u32> {
+            get_manifest(p)
+                .unwrap()
+                .effective_mods()
+                .iter()
+                .map(|m| m.file_id)
+                .collect()
+        };
+        // Moves the resolved fields to the lock
+        write_manifest(&project, &get_manifest(&project).unwrap()).unwrap();
+        let mut m = get_manifest(&project).unwrap();
+        m.name = "two".to_string();
+        let old = m.get_mods().unwrap().iter().next().unwrap().clone();
+        let _ = m.remove_mod(&old);
+        let _ = m.add_mod(
+            manifest(a2)
+                .get_mods()
+                .unwrap()
+                .iter()
+                .next()
+                .unwrap()
+                .clone(),
+        );
+        write_manifest(&project, &m).unwrap();
+        assert_eq!(file_ids(&project), vec![11]);
+        restore_manifest_backup(&project).unwrap();
+        assert_eq!(file_ids(&project), vec![10]);
+        assert_eq!(name(&project), "t");
+        restore_manifest_backup(&project).unwrap();
+        assert_eq!(file_ids(&project), vec![11]);
+        assert_eq!(name(&project), "two");
+    }
+}
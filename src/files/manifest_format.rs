@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{
+    de::DeserializeOwned,
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use std::{ffi::OsStr, path::Path, str::FromStr};
+use yaml_rust::{yaml::Hash, Yaml, YamlEmitter, YamlLoader};
+
+/// File formats a manifest can be written in, picked by the extension of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl ManifestFormat {
+    pub fn from_path(p: &Path) -> Result<Self> {
+        match p.extension().and_then(OsStr::to_str) {
+            Some("yaml") | Some("yml") => Ok(ManifestFormat::Yaml),
+            Some("toml") => Ok(ManifestFormat::Toml),
+            Some("json") => Ok(ManifestFormat::Json),
+            _ => Err(anyhow!(
+                "{} is not a .yaml, .toml or .json file",
+                p.to_string_lossy()
+            )),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ManifestFormat::Yaml => "yaml",
+            ManifestFormat::Toml => "toml",
+            ManifestFormat::Json => "json",
+        }
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(self, s: &str) -> Result<T> {
+        Ok(match self {
+            ManifestFormat::Yaml => serde_yaml::from_str(s)?,
+            ManifestFormat::Toml => toml::from_str(s)?,
+            ManifestFormat::Json => serde_json::from_str(s)?,
+        })
+    }
+
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String> {
+        Ok(match self {
+            ManifestFormat::Yaml => serde_yaml::to_string(value)?,
+            ManifestFormat::Toml => toml::to_string_pretty(value)?,
+            ManifestFormat::Json => {
+                let mut s = serde_json::to_string_pretty(value)?;
+                s.push('\n');
+                s
+            }
+        })
+    }
+
+    /// Parses a document into the yaml-rust tree migrations work on.
+    pub fn to_yaml(self, s: &str) -> Result<Yaml> {
+        Ok(match self {
+            ManifestFormat::Yaml => YamlLoader::load_from_str(s)?
+                .into_iter()
+                .next()
+                .unwrap_or(Yaml::Null),
+            ManifestFormat::Toml => {
+                toml_to_yaml(toml::from_str(s).with_context(|| "could not parse TOML")?)
+            }
+            ManifestFormat::Json => {
+                json_to_yaml(serde_json::from_str(s).with_context(|| "could not parse JSON")?)
+            }
+        })
+    }
+}
+
+/// Reads a yaml-rust tree into `T`, keeping scalars like `1.20` as written.
+pub fn from_yaml<T: DeserializeOwned>(doc: &Yaml) -> Result<T> {
+    let mut s = String::new();
+    YamlEmitter::new(&mut s)
+        .dump(doc)
+        .with_context(|| "could not serialize YAML tree")?;
+    ManifestFormat::Yaml.deserialize(&s)
+}
+
+/// Serializes a yaml-rust tree in any format, keeping the order of its keys.
+#[derive(Debug)]
+pub struct YamlTree<'a>(pub &'a Yaml);
+
+impl Serialize for YamlTree<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.0 {
+            Yaml::Real(r) => match r.parse() {
+                Ok(f) => serializer.serialize_f64(f),
+                Err(_) => serializer.serialize_str(r),
+            },
+            Yaml::Integer(i) => serializer.serialize_i64(*i),
+            Yaml::String(s) => serializer.serialize_str(s),
+            Yaml::Boolean(b) => serializer.serialize_bool(*b),
+            Yaml::Array(a) => {
+                let mut seq = serializer.serialize_seq(Some(a.len()))?;
+                for v in a {
+                    seq.serialize_element(&YamlTree(v))?;
+                }
+                seq.end()
+            }
+            Yaml::Hash(h) => {
+                let mut map = serializer.serialize_map(Some(h.len()))?;
+                for (k, v) in h {
+                    map.serialize_entry(&YamlTree(k), &YamlTree(v))?;
+                }
+                map.end()
+            }
+            Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => serializer.serialize_none(),
+        }
+    }
+}
+
+impl FromStr for ManifestFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "yaml" | "yml" => Ok(ManifestFormat::Yaml),
+            "toml" => Ok(ManifestFormat::Toml),
+            "json" => Ok(ManifestFormat::Json),
+            _ => Err(anyhow!(
+                "{} is not a supported manifest format, use one of yaml, toml or json",
+                s
+            )),
+        }
+    }
+}
+
+fn toml_to_yaml(v: toml::Value) -> Yaml {
+    match v {
+        toml::Value::String(s) => Yaml::String(s),
+        toml::Value::Integer(i) => Yaml::Integer(i),
+        toml::Value::Float(f) => Yaml::Real(f.to_string()),
+        toml::Value::Boolean(b) => Yaml::Boolean(b),
+        toml::Value::Datetime(d) => Yaml::String(d.to_string()),
+        toml::Value::Array(a) => Yaml::Array(a.into_iter().map(toml_to_yaml).collect()),
+        toml::Value::Table(t) => Yaml::Hash(
+            t.into_iter()
+                .map(|(k, v)| (Yaml::String(k), toml_to_yaml(v)))
+                .collect::<Hash>(),
+        ),
+    }
+}
+
+fn json_to_yaml(v: serde_json::Value) -> Yaml {
+    match v {
+        serde_json::Value::Null => Yaml::Null,
+        serde_json::Value::Bool(b) => Yaml::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        serde_json::Value::String(s) => Yaml::String(s),
+        serde_json::Value::Array(a) => Yaml::Array(a.into_iter().map(json_to_yaml).collect()),
+        serde_json::Value::Object(o) => Yaml::Hash(
+            o.into_iter()
+                .map(|(k, v)| (Yaml::String(k), json_to_yaml(v)))
+                .collect::<Hash>(),
+        ),
+    }
+}
@@ -1,15 +1,24 @@
-use crate::files::manifest::{Manifest, Mod};
-use anyhow::{Context, Result};
+use crate::files::{
+    manifest::{Manifest, Mod},
+    mod_loader::ModLoader,
+    versions::{padded_semver, LoaderVersion, MinecraftVersion},
+};
+use anyhow::{anyhow, Context, Result};
 use once_cell::sync::Lazy;
 use semver::Version;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, collections::BTreeSet, io::Write, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    io::{Read, Write},
+    path::PathBuf,
+};
 
 pub static MANIFEST_JSON_FILE: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("manifest.json"));
 
 const MANIFEST_VERSION: u8 = 1;
 const MANIFEST_TYPE: &str = "minecraftModpack";
-pub const MANIFEST_OVERRIDES_FOLDER: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("overrides"));
+pub static MANIFEST_OVERRIDES_FOLDER: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("overrides"));
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -18,13 +27,46 @@ pub struct ManifestJson {
     pub manifest_type: String,
     pub manifest_version: u8,
     pub name: String,
-    pub version: Version,
+    /// As written, since packs made by other tools often do not use semver.
+    pub version: String,
     pub author: String,
     overrides: String,
     files: Option<BTreeSet<FileJson>>,
 }
 
 impl ManifestJson {
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        serde_json::from_reader(reader).with_context(|| "could not deserialize into ManifestJson")
+    }
+
+    /// The version of the pack as semver, padding plain numbers like `1.0` or `v3`.
+    pub fn pack_version(&self) -> Result<Version> {
+        let version = self.version.trim();
+        let version = version.strip_prefix('v').unwrap_or(version);
+        let numbers = version
+            .split('.')
+            .all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()));
+        Version::parse(version)
+            .ok()
+            .or_else(|| {
+                if numbers {
+                    padded_semver(version)
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| anyhow!("pack version {} is not a semver version", self.version))
+    }
+
+    pub fn get_files(&self) -> Option<&BTreeSet<FileJson>> {
+        self.files.as_ref()
+    }
+
+    /// Folder in the zip with the files to copy into the instance.
+    pub fn overrides(&self) -> &str {
+        &self.overrides
+    }
+
     pub fn to_writer<W: Write>(&self, writer: W) -> Result<()> {
         serde_json::to_writer(writer, &self)
             .with_context(|| "could not serialize from MinecrafJson")
@@ -51,19 +93,18 @@ impl From<&Manifest> for ManifestJson {
             overrides: MANIFEST_OVERRIDES_FOLDER.to_string_lossy().into(),
             files: None,
             name: m.name.clone(),
-            version: m.version.clone(),
+            version: m.version.to_string(),
             author: m.author.clone(),
             minecraft: MinecraftJson {
                 version: m.minecraft_version.clone(),
                 mod_loaders: Vec::new(),
             },
         };
-        mj.minecraft
-            .set_mod_loader(&m.mod_loader, &m.mod_loader_version);
-        if let Some(mods) = m.get_mods() {
-            for module in mods {
-                let _ = mj.add_file(module.into());
-            }
+        if let Some(loader) = m.mod_loader {
+            mj.minecraft.set_mod_loader(loader, &m.mod_loader_version);
+        }
+        for module in &m.effective_mods() {
+            let _ = mj.add_file(module.into());
         }
         mj
     }
@@ -72,14 +113,23 @@ impl From<&Manifest> for ManifestJson {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct MinecraftJson {
-    pub version: Version,
+    pub version: MinecraftVersion,
     mod_loaders: Vec<ModLoaderJson>,
 }
 
 impl MinecraftJson {
-    pub fn set_mod_loader(&mut self, name: impl AsRef<str>, version: &Version) {
+    /// The primary mod loader and its version.
+    pub fn get_mod_loader(&self) -> Option<(ModLoader, LoaderVersion)> {
+        self.mod_loaders
+            .iter()
+            .find(|l| l.primary)
+            .or_else(|| self.mod_loaders.first())
+            .and_then(|l| ModLoader::parse_id(&l.id))
+    }
+
+    pub fn set_mod_loader(&mut self, loader: ModLoader, version: &LoaderVersion) {
         self.mod_loaders = vec![ModLoaderJson {
-            id: format!("{}-{}", name.as_ref(), version),
+            id: loader.id(version),
             primary: true,
         }]
     }
@@ -134,3 +184,31 @@ impl PartialOrd for FileJson {
         Some(self.cmp(other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack_version(version: &str) -> Option<String> {
+        let json = format!(
+            r#"{{"minecraft": {{"version": "1.20.1", "modLoaders": []}}, "manifestType": "minecraftModpack",
+                "manifestVersion": 1, "name": "t", "version": "{}", "author": "t", "overrides": "overrides"}}"#,
+            version
+        );
+        ManifestJson::from_reader(json.as_bytes())
+            .unwrap()
+            .pack_version()
+            .ok()
+            .map(|v| v.to_string())
+    }
+
+    #[test]
+    fn pack_versions() {
+        assert_eq!(pack_version("1.2.3-beta"), Some("1.2.3-beta".to_string()));
+        assert_eq!(pack_version("1.2"), Some("1.2.0".to_string()));
+        assert_eq!(pack_version("v3"), Some("3.0.0".to_string()));
+        assert_eq!(pack_version("2023-05"), None);
+        assert_eq!(pack_version("beta"), None);
+        assert_eq!(pack_version("1..2"), None);
+    }
+}
@@ -1,25 +1,23 @@
-use crate::files::manifest_json::ManifestJson;
+use crate::files::{
+    manifest_json::ManifestJson,
+    mod_loader::ModLoader,
+    project::Project,
+    versions::{LoaderVersion, MinecraftVersion},
+};
 use anyhow::{Context, Result};
-use once_cell::sync::Lazy;
-use semver::Version;
 use serde::Deserialize;
 use std::{
     fs::File,
     io::{BufReader, Read},
-    path::PathBuf,
 };
 
-pub static MINECRAFT_INSTANCE_FILE: Lazy<PathBuf> =
-    Lazy::new(|| PathBuf::from("minecraftinstance.json"));
+pub const MINECRAFT_INSTANCE_FILE: &str = "minecraftinstance.json";
 
-pub fn get_minecraft_instance() -> Result<MinecraftInstance> {
+pub fn get_minecraft_instance(project: &Project) -> Result<MinecraftInstance> {
+    let path = project.minecraft_instance_file();
     MinecraftInstance::from_reader(BufReader::new(
-        File::open(Lazy::force(&MINECRAFT_INSTANCE_FILE)).with_context(|| {
-            format!(
-                "could not open {} for reading",
-                MINECRAFT_INSTANCE_FILE.to_string_lossy()
-            )
-        })?,
+        File::open(&path)
+            .with_context(|| format!("could not open {} for reading", path.to_string_lossy()))?,
     ))
 }
 
@@ -28,7 +26,7 @@ pub fn get_minecraft_instance() -> Result<MinecraftInstance> {
 pub struct MinecraftInstance {
     pub name: String,
     pub custom_author: String,
-    pub game_version: Version,
+    pub game_version: MinecraftVersion,
     pub base_mod_loader: BaseModLoader,
     pub manifest: Option<ManifestJson>,
     pub installed_addons: Option<Vec<InstalledAddon>>,
@@ -65,19 +63,7 @@ pub struct BaseModLoader {
 }
 
 impl BaseModLoader {
-    pub fn get_mod_loader(&self) -> Option<(&str, Version)> {
-        match self.name.rfind('-') {
-            None => None,
-            Some(idx) => {
-                let (loader, version) = self.name.split_at(idx);
-                let version = version.trim_start_matches('-');
-                let ver = Version::parse(version);
-                let ver = match ver {
-                    Ok(v) => v,
-                    Err(_) => return None,
-                };
-                Some((loader, ver))
-            }
-        }
+    pub fn get_mod_loader(&self) -> Option<(ModLoader, LoaderVersion)> {
+        ModLoader::parse_id(&self.name)
     }
 }
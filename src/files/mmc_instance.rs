@@ -0,0 +1,222 @@
+use crate::{
+    files::{
+        manifest::Manifest,
+        minecraft_instance::{InstalledAddon, InstalledFile},
+        mod_loader::ModLoader,
+        project::Project,
+        versions::{LoaderVersion, MinecraftVersion},
+    },
+    utils::murmur2::fingerprint,
+};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read, read_dir, read_to_string, File},
+    io::{BufReader, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+pub const MMC_PACK_FILE: &str = "mmc-pack.json";
+pub const INSTANCE_CFG_FILE: &str = "instance.cfg";
+pub const MMC_MINECRAFT_DIR: &str = ".minecraft";
+// Prism keeps packwiz style metadata for the mods it installed in here.
+const MODS_INDEX_DIR: &str = ".index";
+
+const MMC_PACK_FORMAT_VERSION: u8 = 1;
+const MINECRAFT_UID: &str = "net.minecraft";
+
+/// Reads the MultiMC or Prism instance the pack directory belongs to.
+///
+/// Only mods installed from CurseForge are returned, others have no project and file IDs.
+pub fn get_mmc_instance(project: &Project) -> Result<MmcInstance> {
+    let path = project.mmc_pack_file();
+    let pack =
+        MmcPack::from_reader(BufReader::new(File::open(&path).with_context(|| {
+            format!("could not open {} for reading", path.to_string_lossy())
+        })?))?;
+    let cfg = project.instance_cfg_file();
+    let name = if cfg.exists() {
+        let contents = read_to_string(&cfg)
+            .with_context(|| format!("could not read {}", cfg.to_string_lossy()))?;
+        instance_cfg_value(&contents, "name")
+    } else {
+        None
+    };
+    let name = name.unwrap_or_else(|| {
+        project
+            .mmc_instance_dir()
+            .file_name()
+            .map(|n| n.to_string_lossy().into())
+            .unwrap_or_default()
+    });
+    let index = project.mods_dir().join(MODS_INDEX_DIR);
+    let mut installed_addons = Vec::new();
+    if index.is_dir() {
+        for entry in read_dir(&index)
+            .with_context(|| format!("could not read directory {}", index.to_string_lossy()))?
+        {
+            let path = entry?.path();
+            if !path.to_string_lossy().ends_with(".pw.toml") {
+                continue;
+            }
+            if let Some(addon) = installed_addon(&project.mods_dir(), &path)? {
+                installed_addons.push(addon);
+            }
+        }
+    }
+    Ok(MmcInstance {
+        name,
+        pack,
+        installed_addons,
+    })
+}
+
+fn installed_addon(mods_dir: &Path, path: &Path) -> Result<Option<InstalledAddon>> {
+    let contents = read_to_string(path)
+        .with_context(|| format!("could not read {}", path.to_string_lossy()))?;
+    let meta: IndexFile = toml::from_str(&contents)
+        .with_context(|| format!("could not deserialize {}", path.to_string_lossy()))?;
+    let cf = match meta.update.and_then(|u| u.curseforge) {
+        Some(cf) => cf,
+        None => return Ok(None),
+    };
+    let jar = mods_dir.join(&meta.filename);
+    let disabled = mods_dir.join(format!("{}.disabled", meta.filename));
+    let jar = if jar.exists() { jar } else { disabled };
+    if !jar.exists() {
+        println!(
+            "skipping {} as {} is missing",
+            path.to_string_lossy(),
+            meta.filename
+        );
+        return Ok(None);
+    }
+    // The index only has the hash used to download the jar so work out the rest from the jar
+    let buf = read(&jar).with_context(|| format!("could not read {}", jar.to_string_lossy()))?;
+    Ok(Some(InstalledAddon {
+        addon_id: cf.project_id,
+        installed_file: InstalledFile {
+            id: cf.file_id,
+            file_name: meta.filename,
+            file_length: buf.len() as u64,
+            package_fingerprint: fingerprint(&buf),
+        },
+    }))
+}
+
+// instance.cfg is an ini file written by Qt, only simple values are needed from it.
+fn instance_cfg_value(contents: &str, key: &str) -> Option<String> {
+    contents
+        .lines()
+        .filter_map(|l| {
+            let mut kv = l.splitn(2, '=');
+            Some((kv.next()?.trim(), kv.next()?.trim()))
+        })
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.to_string())
+}
+
+/// Writes an instance.cfg for the pack.
+pub fn write_instance_cfg<W: Write>(mut writer: W, manifest: &Manifest) -> Result<()> {
+    write!(
+        writer,
+        "[General]\nInstanceType=OneSix\nname={}\n",
+        manifest.name
+    )
+    .with_context(|| "could not write instance.cfg")
+}
+
+#[derive(Debug)]
+pub struct MmcInstance {
+    pub name: String,
+    pub pack: MmcPack,
+    pub installed_addons: Vec<InstalledAddon>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MmcPack {
+    components: Vec<Component>,
+    format_version: u8,
+}
+
+impl MmcPack {
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        serde_json::from_reader(reader).with_context(|| "could not deserialize into MmcPack")
+    }
+
+    pub fn to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, &self)
+            .with_context(|| "could not serialize from MmcPack")
+    }
+
+    /// Components for the Minecraft and mod loader versions of the manifest.
+    pub fn from_manifest(m: &Manifest) -> Result<Self> {
+        let loader = m
+            .mod_loader
+            .ok_or_else(|| anyhow!("modLoader needs to be set for MultiMC"))?;
+        Ok(MmcPack {
+            components: vec![
+                Component {
+                    uid: MINECRAFT_UID.to_string(),
+                    version: Some(m.minecraft_version.to_string()),
+                    important: Some(true),
+                },
+                Component {
+                    uid: loader.mmc_uid().to_string(),
+                    version: Some(m.mod_loader_version.to_string()),
+                    important: None,
+                },
+            ],
+            format_version: MMC_PACK_FORMAT_VERSION,
+        })
+    }
+
+    pub fn minecraft_version(&self) -> Option<MinecraftVersion> {
+        self.component_version(MINECRAFT_UID)
+    }
+
+    /// The first mod loader in the components and its version.
+    pub fn get_mod_loader(&self) -> Option<(ModLoader, LoaderVersion)> {
+        ModLoader::ALL
+            .iter()
+            .find_map(|l| Some((*l, self.component_version(l.mmc_uid())?)))
+    }
+
+    fn component_version<V: FromStr>(&self, uid: &str) -> Option<V> {
+        self.components
+            .iter()
+            .find(|c| c.uid == uid)
+            .and_then(|c| c.version.as_ref())
+            .and_then(|v| v.parse().ok())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Component {
+    uid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    important: Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+struct IndexFile {
+    filename: String,
+    update: Option<IndexUpdate>,
+}
+
+#[derive(Deserialize, Debug)]
+struct IndexUpdate {
+    curseforge: Option<IndexCurseForge>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct IndexCurseForge {
+    file_id: u32,
+    project_id: u32,
+}
@@ -1,10 +1,11 @@
+pub mod lockfile;
 pub mod manifest;
+pub mod manifest_format;
 pub mod manifest_json;
 pub mod minecraft_instance;
-
-use once_cell::sync::Lazy;
-use std::path::PathBuf;
-
-pub static MODS_DIR: Lazy<PathBuf> = Lazy::new(|| PathBuf::from("mods"));
-pub static CWD: Lazy<PathBuf> =
-    Lazy::new(|| std::env::current_dir().unwrap().canonicalize().unwrap());
+pub mod mmc_instance;
+pub mod mod_loader;
+pub mod mod_metadata;
+pub mod project;
+pub mod versions;
+pub mod workspace;
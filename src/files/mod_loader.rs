@@ -0,0 +1,191 @@
+use crate::files::versions::LoaderVersion;
+use anyhow::anyhow;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject},
+    JsonSchema,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// Mod loaders a pack can be made for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModLoader {
+    Forge,
+    NeoForge,
+    Fabric,
+    Quilt,
+}
+
+impl ModLoader {
+    pub const ALL: [ModLoader; 4] = [
+        ModLoader::Forge,
+        ModLoader::NeoForge,
+        ModLoader::Fabric,
+        ModLoader::Quilt,
+    ];
+
+    /// Name used in the manifest and in loader IDs.
+    pub fn name(self) -> &'static str {
+        match self {
+            ModLoader::Forge => "forge",
+            ModLoader::NeoForge => "neoforge",
+            ModLoader::Fabric => "fabric",
+            ModLoader::Quilt => "quilt",
+        }
+    }
+
+    /// ID of the loader in a CurseForge manifest.json, like `fabric-0.14.21`.
+    pub fn id(self, version: &LoaderVersion) -> String {
+        format!("{}-{}", self.name(), version)
+    }
+
+    /// Parses loader IDs written by launchers.
+    ///
+    /// Besides `forge-36.1.0`, Fabric and Quilt IDs can end with the Minecraft version,
+    /// like `fabric-0.14.21-1.19.2`, and older Forge IDs start with it, like
+    /// `forge-1.12.2-14.23.5.2860`.
+    pub fn parse_id(id: &str) -> Option<(ModLoader, LoaderVersion)> {
+        let mut parts = id.trim().splitn(2, '-');
+        let loader: ModLoader = parts.next()?.parse().ok()?;
+        let rest = parts.next()?;
+        let mut rest_parts = rest.splitn(2, '-');
+        let first = rest_parts.next()?;
+        let version = match (loader, rest_parts.next()) {
+            (ModLoader::Fabric, Some(_)) | (ModLoader::Quilt, Some(_)) => first,
+            (ModLoader::Forge, Some(v)) | (ModLoader::NeoForge, Some(v))
+                if first.starts_with("1.") =>
+            {
+                v
+            }
+            _ => rest,
+        };
+        Some((loader, version.parse().ok()?))
+    }
+
+    /// UID of the loader component in a MultiMC mmc-pack.json.
+    pub fn mmc_uid(self) -> &'static str {
+        match self {
+            ModLoader::Forge => "net.minecraftforge",
+            ModLoader::NeoForge => "net.neoforged",
+            ModLoader::Fabric => "net.fabricmc.fabric-loader",
+            ModLoader::Quilt => "org.quiltmc.quilt-loader",
+        }
+    }
+
+    /// Mod ID jars use to depend on the loader.
+    pub fn dependency_id(self) -> &'static str {
+        match self {
+            ModLoader::Forge => "forge",
+            ModLoader::NeoForge => "neoforge",
+            ModLoader::Fabric => "fabricloader",
+            ModLoader::Quilt => "quilt_loader",
+        }
+    }
+}
+
+impl FromStr for ModLoader {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        ModLoader::ALL
+            .iter()
+            .copied()
+            .find(|l| l.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                anyhow!(
+                    "{} is not a supported mod loader, use one of forge, neoforge, fabric or quilt",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for ModLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Serialize for ModLoader {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl JsonSchema for ModLoader {
+    fn schema_name() -> String {
+        "ModLoader".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(ModLoader::ALL.iter().map(|l| l.name().into()).collect()),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl<'de> Deserialize<'de> for ModLoader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Reads an optional mod loader, where an empty name is no mod loader like older manifests have.
+pub fn deserialize_optional<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<ModLoader>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) if !s.trim().is_empty() => s.parse().map(Some).map_err(de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(id: &str) -> Option<(ModLoader, String)> {
+        ModLoader::parse_id(id).map(|(l, v)| (l, v.to_string()))
+    }
+
+    #[test]
+    fn parse_ids() {
+        assert_eq!(
+            parsed("forge-47.2.0"),
+            Some((ModLoader::Forge, "47.2.0".to_string()))
+        );
+        assert_eq!(
+            parsed("fabric-0.14.21-1.19.2"),
+            Some((ModLoader::Fabric, "0.14.21".to_string()))
+        );
+        assert_eq!(
+            parsed("forge-1.12.2-14.23.5.2860"),
+            Some((ModLoader::Forge, "14.23.5.2860".to_string()))
+        );
+        assert_eq!(
+            parsed("neoforge-20.4.80-beta"),
+            Some((ModLoader::NeoForge, "20.4.80-beta".to_string()))
+        );
+        assert_eq!(
+            parsed("quilt-0.19.2-1.20.1"),
+            Some((ModLoader::Quilt, "0.19.2".to_string()))
+        );
+        assert_eq!(
+            parsed("quilt-0.19.2"),
+            Some((ModLoader::Quilt, "0.19.2".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_unknown_ids() {
+        assert_eq!(parsed("rift-1.0.0"), None);
+        assert_eq!(parsed("forge"), None);
+        assert_eq!(parsed(""), None);
+    }
+}
@@ -0,0 +1,715 @@
+use crate::files::{
+    manifest::{clean_path, Manifest},
+    project::Project,
+};
+use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fs::{read_dir, File},
+    io::{BufReader, Cursor, Read, Seek},
+    path::Path,
+    str::FromStr,
+};
+use zip::{result::ZipError, ZipArchive};
+
+const FORGE_MODS_TOML: &str = "META-INF/mods.toml";
+const NEOFORGE_MODS_TOML: &str = "META-INF/neoforge.mods.toml";
+const JAR_MANIFEST: &str = "META-INF/MANIFEST.MF";
+const FABRIC_MOD_JSON: &str = "fabric.mod.json";
+const QUILT_MOD_JSON: &str = "quilt.mod.json";
+const MCMOD_INFO: &str = "mcmod.info";
+const JARJAR_METADATA: &str = "META-INF/jarjar/metadata.json";
+// Version of a provided mod that is not known.
+const ANY_VERSION: &str = "*";
+
+/// A mod declared by a jar.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModMetadata {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub side: Option<ModSide>,
+    /// Mod IDs that are required and the versions they need to be, as written by the mod.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<BTreeMap<String, String>>,
+    /// Other mod IDs the jar satisfies, from aliases and jars bundled inside it, and their versions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provides: Option<BTreeMap<String, String>>,
+}
+
+impl ModMetadata {
+    fn new(id: String) -> Self {
+        ModMetadata {
+            id,
+            name: None,
+            version: None,
+            side: None,
+            dependencies: None,
+            provides: None,
+        }
+    }
+
+    fn add_provides(&mut self, id: String, version: String) {
+        if id != self.id {
+            let _ = self
+                .provides
+                .get_or_insert_with(BTreeMap::new)
+                .insert(id, version);
+        }
+    }
+
+    fn add_dependency(&mut self, id: String, version: String) {
+        let _ = self
+            .dependencies
+            .get_or_insert_with(BTreeMap::new)
+            .insert(id, version);
+    }
+}
+
+/// Where a mod needs to be installed.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ModSide {
+    Client,
+    Server,
+    Both,
+}
+
+impl FromStr for ModSide {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "client" => Ok(ModSide::Client),
+            "server" => Ok(ModSide::Server),
+            "both" => Ok(ModSide::Both),
+            _ => Err(anyhow!("{} is not a side, use client, server or both", s)),
+        }
+    }
+}
+
+/// Reads the mods declared in the metadata files of a jar.
+///
+/// Jars made for more than one loader can declare the same mod more than once,
+/// only the first one found is kept.
+pub fn read_mod_metadata<R: Read + Seek>(reader: R) -> Result<Vec<ModMetadata>> {
+    let mut jar = ZipArchive::new(reader).with_context(|| "not a valid jar")?;
+    let mut mods: Vec<ModMetadata> = Vec::new();
+    let mut found = Vec::new();
+    let mut nested = Vec::new();
+    for name in &[FORGE_MODS_TOML, NEOFORGE_MODS_TOML] {
+        if let Some(contents) = read_entry(&mut jar, name)? {
+            let jar_version = read_entry(&mut jar, JAR_MANIFEST)?
+                .and_then(|m| manifest_value(&m, "Implementation-Version"));
+            found.extend(
+                parse_mods_toml(&contents, jar_version.as_deref())
+                    .with_context(|| format!("could not parse {}", name))?,
+            );
+        }
+    }
+    if let Some(contents) = read_entry(&mut jar, JARJAR_METADATA)? {
+        let json: JarJarMetadata = serde_json::from_str(&contents)
+            .with_context(|| format!("could not parse {}", JARJAR_METADATA))?;
+        nested.extend(json.jars.into_iter().map(|j| j.path));
+    }
+    if let Some(contents) = read_entry(&mut jar, FABRIC_MOD_JSON)? {
+        let (meta, jars) = parse_fabric_mod_json(&contents)
+            .with_context(|| format!("could not parse {}", FABRIC_MOD_JSON))?;
+        found.push(meta);
+        nested.extend(jars);
+    }
+    if let Some(contents) = read_entry(&mut jar, QUILT_MOD_JSON)? {
+        let (meta, jars) = parse_quilt_mod_json(&contents)
+            .with_context(|| format!("could not parse {}", QUILT_MOD_JSON))?;
+        found.push(meta);
+        nested.extend(jars);
+    }
+    if let Some(contents) = read_entry(&mut jar, MCMOD_INFO)? {
+        found.extend(
+            parse_mcmod_info(&contents)
+                .with_context(|| format!("could not parse {}", MCMOD_INFO))?,
+        );
+    }
+    for m in found {
+        if !mods.iter().any(|f| f.id == m.id) {
+            mods.push(m);
+        }
+    }
+    // Bundled jars are loaded as part of the jar, so they only satisfy dependencies
+    // and do not conflict with other jars
+    if let Some(first) = mods.first_mut() {
+        for path in nested {
+            let buf = match read_entry_bytes(&mut jar, &path)? {
+                Some(buf) => buf,
+                None => continue,
+            };
+            for m in read_mod_metadata(Cursor::new(buf))
+                .with_context(|| format!("could not read bundled jar {}", path))?
+            {
+                for (id, version) in m.provides.into_iter().flatten() {
+                    first.add_provides(id, version);
+                }
+                first.add_provides(m.id, m.version.unwrap_or_else(|| ANY_VERSION.to_string()));
+            }
+        }
+    }
+    Ok(mods)
+}
+
+/// Reads the mods declared by the jar at `path`.
+pub fn read_jar_metadata(path: &Path) -> Result<Vec<ModMetadata>> {
+    read_mod_metadata(BufReader::new(File::open(path).with_context(|| {
+        format!("could not open {} for reading", path.to_string_lossy())
+    })?))
+    .with_context(|| format!("could not read metadata of {}", path.to_string_lossy()))
+}
+
+/// A jar that will be loaded and the mods it declares.
+#[derive(Debug)]
+pub struct ModJar {
+    /// The jar and where it comes from, like the project it was downloaded from.
+    pub source: String,
+    pub mods: Vec<ModMetadata>,
+}
+
+impl ModJar {
+    pub fn tracked(file: impl AsRef<Path>, project_id: u32, mods: Vec<ModMetadata>) -> Self {
+        ModJar {
+            source: format!("{} (project {})", clean_path(file), project_id),
+            mods,
+        }
+    }
+
+    pub fn included(file: impl AsRef<Path>, mods: Vec<ModMetadata>) -> Self {
+        ModJar {
+            source: format!("{} (included)", clean_path(file)),
+            mods,
+        }
+    }
+}
+
+/// Jars in the mods folder that will be loaded.
+///
+/// Tracked mods use the metadata in the manifest when it has been read before.
+/// Jars that can not be read are reported and left out.
+pub fn installed_mod_jars(project: &Project, manifest: &Manifest) -> Result<Vec<ModJar>> {
+    let mods_dir = project.mods_dir();
+    let mut jars = Vec::new();
+    if !mods_dir.is_dir() {
+        return Ok(jars);
+    }
+    let tracked_mods = manifest.mods_by_filename();
+    for entry in read_dir(&mods_dir)
+        .with_context(|| format!("could not read directory {}", mods_dir.to_string_lossy()))?
+    {
+        let path = entry?.path();
+        // Disabled jars are not loaded so they can not conflict
+        if path.is_dir() || path.extension() != Some(OsStr::new("jar")) {
+            continue;
+        }
+        let rel = path.strip_prefix(project.root()).unwrap_or(&path);
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tracked = tracked_mods.get(file_name.as_ref());
+        let mods = match tracked.as_ref().and_then(|m| m.metadata.clone()) {
+            Some(mods) => mods,
+            None => match read_jar_metadata(&path) {
+                Ok(mods) => mods,
+                Err(e) => {
+                    println!("warning: {:#}", e);
+                    continue;
+                }
+            },
+        };
+        jars.push(match tracked {
+            Some(m) => ModJar::tracked(rel, m.project_id, mods),
+            None if manifest.is_included(rel, false)? => ModJar::included(rel, mods),
+            None => ModJar {
+                source: format!("{} (untracked)", clean_path(rel)),
+                mods,
+            },
+        });
+    }
+    Ok(jars)
+}
+
+/// Describes every mod ID declared by more than one jar.
+///
+/// Forge refuses to load when two jars have the same mod ID.
+pub fn duplicate_mod_ids(jars: &[ModJar]) -> Vec<String> {
+    let mut ids: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for jar in jars {
+        for m in &jar.mods {
+            ids.entry(&m.id).or_default().push(&jar.source);
+        }
+    }
+    ids.into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .map(|(id, sources)| {
+            format!(
+                "mod ID {} is declared by more than one jar: {}",
+                id,
+                sources.join(", ")
+            )
+        })
+        .collect()
+}
+
+fn read_entry<R: Read + Seek>(jar: &mut ZipArchive<R>, name: &str) -> Result<Option<String>> {
+    Ok(read_entry_bytes(jar, name)?.map(|buf| String::from_utf8_lossy(&buf).into_owned()))
+}
+
+fn read_entry_bytes<R: Read + Seek>(
+    jar: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>> {
+    let mut file = match jar.by_name(name) {
+        Ok(f) => f,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("could not read {}", name)),
+    };
+    let mut buf = Vec::new();
+    let _ = file
+        .read_to_end(&mut buf)
+        .with_context(|| format!("could not read {}", name))?;
+    Ok(Some(buf))
+}
+
+// Values in MANIFEST.MF are "Key: value" lines.
+fn manifest_value(contents: &str, key: &str) -> Option<String> {
+    contents
+        .lines()
+        .filter_map(|l| {
+            let mut kv = l.splitn(2, ':');
+            Some((kv.next()?.trim(), kv.next()?.trim()))
+        })
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.to_string())
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ModsToml {
+    #[serde(default)]
+    mods: Vec<ModsTomlMod>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Vec<ModsTomlDependency>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlMod {
+    mod_id: String,
+    version: Option<String>,
+    display_name: Option<String>,
+    display_test: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlDependency {
+    mod_id: String,
+    // Forge uses mandatory and NeoForge uses type
+    mandatory: Option<bool>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    version_range: Option<String>,
+}
+
+fn parse_mods_toml(contents: &str, jar_version: Option<&str>) -> Result<Vec<ModMetadata>> {
+    let toml: ModsToml = toml::from_str(contents)?;
+    let mut mods = Vec::new();
+    for m in toml.mods {
+        let mut meta = ModMetadata::new(m.mod_id);
+        meta.name = m.display_name;
+        meta.version = match (m.version, jar_version) {
+            (Some(v), Some(jar)) if v == "${file.jarVersion}" => Some(jar.to_string()),
+            (Some(v), None) if v == "${file.jarVersion}" => None,
+            (v, _) => v,
+        };
+        // Mods that do not care if the other side has them are usually only needed on one side
+        meta.side = match m.display_test.as_deref() {
+            Some("IGNORE_ALL_VERSION") => Some(ModSide::Client),
+            Some("IGNORE_SERVER_VERSION") => Some(ModSide::Server),
+            _ => None,
+        };
+        for dep in toml.dependencies.get(&meta.id).into_iter().flatten() {
+            let required = match (&dep.kind, dep.mandatory) {
+                (Some(kind), _) => kind.eq_ignore_ascii_case("required"),
+                (None, Some(mandatory)) => mandatory,
+                (None, None) => true,
+            };
+            if required {
+                meta.add_dependency(
+                    dep.mod_id.clone(),
+                    dep.version_range.clone().unwrap_or_else(|| "*".to_string()),
+                );
+            }
+        }
+        mods.push(meta);
+    }
+    Ok(mods)
+}
+
+#[derive(Deserialize, Debug)]
+struct FabricModJson {
+    id: String,
+    version: Option<String>,
+    name: Option<String>,
+    environment: Option<String>,
+    #[serde(default)]
+    depends: BTreeMap<String, Value>,
+    #[serde(default)]
+    provides: Vec<String>,
+    #[serde(default)]
+    jars: Vec<FabricJar>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FabricJar {
+    file: String,
+}
+
+// Also returns the paths of the jars bundled in the jar.
+fn parse_fabric_mod_json(contents: &str) -> Result<(ModMetadata, Vec<String>)> {
+    let json: FabricModJson = serde_json::from_str(contents)?;
+    let mut meta = ModMetadata::new(json.id);
+    meta.name = json.name;
+    meta.version = json.version;
+    meta.side = match json.environment.as_deref() {
+        Some("client") => Some(ModSide::Client),
+        Some("server") => Some(ModSide::Server),
+        Some("*") => Some(ModSide::Both),
+        _ => None,
+    };
+    for (id, versions) in json.depends {
+        meta.add_dependency(id, version_ranges(&versions));
+    }
+    let version = meta
+        .version
+        .clone()
+        .unwrap_or_else(|| ANY_VERSION.to_string());
+    for id in json.provides {
+        meta.add_provides(id, version.clone());
+    }
+    Ok((meta, json.jars.into_iter().map(|j| j.file).collect()))
+}
+
+#[derive(Deserialize, Debug)]
+struct QuiltModJson {
+    quilt_loader: QuiltLoader,
+    minecraft: Option<QuiltMinecraft>,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuiltLoader {
+    id: String,
+    version: Option<String>,
+    metadata: Option<QuiltMetadata>,
+    #[serde(default)]
+    depends: Vec<Value>,
+    #[serde(default)]
+    provides: Vec<Value>,
+    #[serde(default)]
+    jars: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuiltMetadata {
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuiltMinecraft {
+    environment: Option<String>,
+}
+
+// Also returns the paths of the jars bundled in the jar.
+fn parse_quilt_mod_json(contents: &str) -> Result<(ModMetadata, Vec<String>)> {
+    let json: QuiltModJson = serde_json::from_str(contents)?;
+    let loader = json.quilt_loader;
+    let mut meta = ModMetadata::new(loader.id);
+    meta.name = loader.metadata.and_then(|m| m.name);
+    meta.version = loader.version;
+    meta.side = match json.minecraft.and_then(|m| m.environment).as_deref() {
+        Some("client") => Some(ModSide::Client),
+        Some("dedicated_server") => Some(ModSide::Server),
+        Some("*") => Some(ModSide::Both),
+        _ => None,
+    };
+    for dep in loader.depends {
+        match dep {
+            Value::String(id) => meta.add_dependency(id, "*".to_string()),
+            Value::Object(o) => {
+                if o.get("optional").and_then(Value::as_bool) == Some(true) {
+                    continue;
+                }
+                if let Some(id) = o.get("id").and_then(Value::as_str) {
+                    let versions = o
+                        .get("versions")
+                        .map_or_else(|| "*".to_string(), version_ranges);
+                    meta.add_dependency(id.to_string(), versions);
+                }
+            }
+            _ => {}
+        }
+    }
+    let version = meta
+        .version
+        .clone()
+        .unwrap_or_else(|| ANY_VERSION.to_string());
+    for p in loader.provides {
+        match p {
+            Value::String(id) => meta.add_provides(id, version.clone()),
+            Value::Object(o) => {
+                if let Some(id) = o.get("id").and_then(Value::as_str) {
+                    let v = o.get("version").and_then(Value::as_str).unwrap_or(&version);
+                    meta.add_provides(id.to_string(), v.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((meta, loader.jars))
+}
+
+// Fabric and Quilt allow a list of versions where any of them will do.
+fn version_ranges(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        Value::Array(a) => a
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" || "),
+        _ => "*".to_string(),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct JarJarMetadata {
+    #[serde(default)]
+    jars: Vec<JarJarEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct JarJarEntry {
+    path: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum McmodInfo {
+    List(Vec<McmodInfoMod>),
+    // Version 2 of the format wraps the list
+    Wrapped {
+        #[serde(rename = "modList")]
+        mod_list: Vec<McmodInfoMod>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct McmodInfoMod {
+    modid: String,
+    name: Option<String>,
+    version: Option<String>,
+    #[serde(default)]
+    required_mods: Vec<String>,
+}
+
+fn parse_mcmod_info(contents: &str) -> Result<Vec<ModMetadata>> {
+    let info: McmodInfo = serde_json::from_str(contents)?;
+    let list = match info {
+        McmodInfo::List(l) => l,
+        McmodInfo::Wrapped { mod_list } => mod_list,
+    };
+    let mut mods = Vec::new();
+    for m in list {
+        let mut meta = ModMetadata::new(m.modid);
+        meta.name = m.name;
+        meta.version = m.version;
+        // Required mods are written as modid@versionRange
+        for req in m.required_mods {
+            let mut parts = req.splitn(2, '@');
+            let id = parts.next().unwrap_or_default().trim().to_string();
+            let version = parts.next().unwrap_or("*").trim().to_string();
+            if !id.is_empty() {
+                meta.add_dependency(id, version);
+            }
+        }
+        mods.push(meta);
+    }
+    Ok(mods)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::{write::FileOptions, ZipWriter};
+
+    fn jar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn mods_toml_jar_version() {
+        let toml = r#"
+[[mods]]
+modId = "a"
+version = "${file.jarVersion}"
+"#;
+        let mods = parse_mods_toml(toml, Some("1.2.3")).unwrap();
+        assert_eq!(mods[0].version.as_deref(), Some("1.2.3"));
+        let mods = parse_mods_toml(toml, None).unwrap();
+        assert_eq!(mods[0].version, None);
+        let buf = jar(&[
+            (FORGE_MODS_TOML, toml.as_bytes()),
+            (
+                JAR_MANIFEST,
+                b"Manifest-Version: 1.0\nImplementation-Version: 4.5\n",
+            ),
+        ]);
+        let mods = read_mod_metadata(Cursor::new(buf)).unwrap();
+        assert_eq!(mods[0].version.as_deref(), Some("4.5"));
+    }
+
+    #[test]
+    fn mods_toml_display_test_side() {
+        let toml = r#"
+[[mods]]
+modId = "client"
+displayTest = "IGNORE_ALL_VERSION"
+[[mods]]
+modId = "server"
+displayTest = "IGNORE_SERVER_VERSION"
+[[mods]]
+modId = "both"
+displayTest = "MATCH_VERSION"
+"#;
+        let sides: Vec<_> = parse_mods_toml(toml, None)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.side)
+            .collect();
+        assert_eq!(
+            sides,
+            vec![Some(ModSide::Client), Some(ModSide::Server), None]
+        );
+    }
+
+    #[test]
+    fn mods_toml_required_dependencies() {
+        let toml = r#"
+[[mods]]
+modId = "a"
+[[dependencies.a]]
+modId = "forge"
+mandatory = true
+versionRange = "[47,)"
+[[dependencies.a]]
+modId = "jei"
+mandatory = false
+[[dependencies.a]]
+modId = "neoforge"
+type = "required"
+[[dependencies.a]]
+modId = "curios"
+type = "optional"
+[[dependencies.a]]
+modId = "minecraft"
+"#;
+        let deps = parse_mods_toml(toml, None).unwrap()[0]
+            .dependencies
+            .clone()
+            .unwrap();
+        let expected: BTreeMap<String, String> = vec![
+            ("forge".to_string(), "[47,)".to_string()),
+            ("minecraft".to_string(), "*".to_string()),
+            ("neoforge".to_string(), "*".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(deps, expected);
+    }
+
+    #[test]
+    fn fabric_provides_and_nested_jars() {
+        let json = r#"{
+            "id": "a",
+            "version": "1.0.0",
+            "environment": "client",
+            "depends": {"fabricloader": ">=0.14", "minecraft": ["1.20", "1.20.1"]},
+            "provides": ["a_alias"],
+            "jars": [{"file": "META-INF/jars/b.jar"}]
+        }"#;
+        let (meta, jars) = parse_fabric_mod_json(json).unwrap();
+        assert_eq!(meta.side, Some(ModSide::Client));
+        let deps = meta.dependencies.unwrap();
+        assert_eq!(deps["minecraft"], "1.20 || 1.20.1");
+        assert_eq!(meta.provides.unwrap()["a_alias"], "1.0.0");
+        assert_eq!(jars, vec!["META-INF/jars/b.jar"]);
+
+        let nested = jar(&[(FABRIC_MOD_JSON, br#"{"id": "b", "version": "2.0"}"#)]);
+        let buf = jar(&[
+            (FABRIC_MOD_JSON, json.as_bytes()),
+            ("META-INF/jars/b.jar", &nested),
+        ]);
+        let mods = read_mod_metadata(Cursor::new(buf)).unwrap();
+        assert_eq!(mods.len(), 1);
+        assert_eq!(mods[0].provides.as_ref().unwrap()["b"], "2.0");
+    }
+
+    #[test]
+    fn quilt_provides_and_nested_jars() {
+        let json = r#"{
+            "quilt_loader": {
+                "id": "a",
+                "version": "1.0.0",
+                "metadata": {"name": "A"},
+                "depends": ["quilt_loader", {"id": "b", "versions": ">=2"}, {"id": "c", "optional": true}],
+                "provides": ["a_alias", {"id": "a_api", "version": "0.5"}],
+                "jars": ["META-INF/jars/b.jar"]
+            },
+            "minecraft": {"environment": "dedicated_server"}
+        }"#;
+        let (meta, jars) = parse_quilt_mod_json(json).unwrap();
+        assert_eq!(meta.name.as_deref(), Some("A"));
+        assert_eq!(meta.side, Some(ModSide::Server));
+        let deps = meta.dependencies.unwrap();
+        assert_eq!(deps.keys().collect::<Vec<_>>(), vec!["b", "quilt_loader"]);
+        assert_eq!(deps["b"], ">=2");
+        let provides = meta.provides.unwrap();
+        assert_eq!(provides["a_alias"], "1.0.0");
+        assert_eq!(provides["a_api"], "0.5");
+        assert_eq!(jars, vec!["META-INF/jars/b.jar"]);
+    }
+
+    #[test]
+    fn mcmod_info_formats() {
+        let list =
+            r#"[{"modid": "a", "name": "A", "version": "1.0", "requiredMods": ["b@[2.0,)", "c"]}]"#;
+        let wrapped = r#"{"modListVersion": 2, "modList": [{"modid": "a", "name": "A", "version": "1.0", "requiredMods": ["b@[2.0,)", "c"]}]}"#;
+        for contents in &[list, wrapped] {
+            let mods = parse_mcmod_info(contents).unwrap();
+            assert_eq!(mods.len(), 1);
+            assert_eq!(mods[0].id, "a");
+            let deps = mods[0].dependencies.as_ref().unwrap();
+            assert_eq!(deps["b"], "[2.0,)");
+            assert_eq!(deps["c"], "*");
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::files::{
+    lockfile::lock_path,
+    manifest::{MANIFEST_FILE, MANIFEST_FILES},
+    minecraft_instance::MINECRAFT_INSTANCE_FILE,
+    mmc_instance::{INSTANCE_CFG_FILE, MMC_PACK_FILE},
+};
+use anyhow::{anyhow, Context, Result};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+const MODS_DIR: &str = "mods";
+
+/// Locations of the files that make up a single mod pack.
+///
+/// Every command works against a project instead of the process directory
+/// so packs can be managed from anywhere.
+#[derive(Debug, Clone)]
+pub struct Project {
+    root: PathBuf,
+    manifest_file: PathBuf,
+}
+
+impl Project {
+    /// Relative paths are resolved against the current directory for `dir`
+    /// and against the pack directory for `manifest`.
+    ///
+    /// Without `manifest`, whichever of .manifest.yaml, .manifest.toml,
+    /// .manifest.json or their mcpacker.* counterparts is in the pack directory is used.
+    pub fn new(dir: Option<&Path>, manifest: Option<&Path>) -> Result<Self> {
+        let cwd = std::env::current_dir().with_context(|| "could not get current directory")?;
+        let root = match dir {
+            Some(d) => cwd.join(d),
+            None => cwd,
+        };
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("could not normalize {}", root.to_string_lossy()))?;
+        let manifest_file = match manifest {
+            Some(m) => root.join(m),
+            None => find_manifest(&root)?,
+        };
+        Ok(Project {
+            root,
+            manifest_file,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn manifest_file(&self) -> &Path {
+        &self.manifest_file
+    }
+
+    pub fn manifest_backup_file(&self) -> PathBuf {
+        with_suffix(&self.manifest_file, ".bak")
+    }
+
+    pub fn manifest_tmp_file(&self) -> PathBuf {
+        with_suffix(&self.manifest_file, ".tmp")
+    }
+
+    /// Lock file of an `mcpacker` manifest, next to it.
+    pub fn lock_file(&self) -> PathBuf {
+        lock_path(&self.manifest_file)
+    }
+
+    pub fn lock_backup_file(&self) -> PathBuf {
+        with_suffix(&self.lock_file(), ".bak")
+    }
+
+    pub fn lock_tmp_file(&self) -> PathBuf {
+        with_suffix(&self.lock_file(), ".tmp")
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.root.join(MODS_DIR)
+    }
+
+    pub fn minecraft_instance_file(&self) -> PathBuf {
+        self.root.join(MINECRAFT_INSTANCE_FILE)
+    }
+
+    /// Folder MultiMC and Prism keep the instance files in.
+    ///
+    /// The pack directory is the .minecraft folder of the instance so mods and
+    /// configs are laid out the same as in a CurseForge instance.
+    pub fn mmc_instance_dir(&self) -> &Path {
+        self.root.parent().unwrap_or(&self.root)
+    }
+
+    pub fn mmc_pack_file(&self) -> PathBuf {
+        self.mmc_instance_dir().join(MMC_PACK_FILE)
+    }
+
+    pub fn instance_cfg_file(&self) -> PathBuf {
+        self.mmc_instance_dir().join(INSTANCE_CFG_FILE)
+    }
+
+    /// Launcher that manages the instance the pack is in, if any.
+    pub fn launcher(&self) -> Option<Launcher> {
+        if self.minecraft_instance_file().exists() {
+            Some(Launcher::CurseForge)
+        } else if self.mmc_pack_file().exists() {
+            Some(Launcher::Prism)
+        } else {
+            None
+        }
+    }
+
+    /// Path of `p` relative to the pack directory.
+    ///
+    /// `p` itself is resolved against the pack directory so it must exist.
+    pub fn relative_path(&self, p: impl AsRef<Path>) -> Result<PathBuf> {
+        let p = p.as_ref();
+        let abs = self
+            .root
+            .join(p)
+            .canonicalize()
+            .with_context(|| format!("could not normalize {}", p.to_string_lossy()))?;
+        Ok(abs
+            .strip_prefix(&self.root)
+            .with_context(|| {
+                format!(
+                    "{} is not in {}",
+                    p.to_string_lossy(),
+                    self.root.to_string_lossy()
+                )
+            })?
+            .to_path_buf())
+    }
+}
+
+/// Launchers an instance can be read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    CurseForge,
+    /// MultiMC and Prism Launcher share the same instance layout.
+    Prism,
+}
+
+impl FromStr for Launcher {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "curseforge" => Ok(Launcher::CurseForge),
+            "prism" | "multimc" => Ok(Launcher::Prism),
+            _ => Err(anyhow!("{} is not a supported launcher", s)),
+        }
+    }
+}
+
+fn find_manifest(root: &Path) -> Result<PathBuf> {
+    let found: Vec<&str> = MANIFEST_FILES
+        .iter()
+        .copied()
+        .filter(|f| root.join(f).exists())
+        .collect();
+    match found.as_slice() {
+        [] => Ok(root.join(MANIFEST_FILE)),
+        [f] => Ok(root.join(f)),
+        _ => Err(anyhow!(
+            "found {} in {}, remove all but one or pick one with --manifest",
+            found.join(" and "),
+            root.to_string_lossy()
+        )),
+    }
+}
+
+fn with_suffix(p: &Path, suffix: &str) -> PathBuf {
+    let mut name = p.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    p.with_file_name(name)
+}
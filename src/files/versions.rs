@@ -0,0 +1,288 @@
+use crate::utils::version_range::compare_versions;
+use anyhow::anyhow;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, Schema, SchemaObject},
+    JsonSchema,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+// Written when a version is not known, like older manifests do.
+const UNKNOWN_VERSION: &str = "0.0.0";
+
+/// Minecraft version exactly as Mojang names it, like `1.20`, `1.20.1-rc1` or `23w13a`.
+///
+/// Releases are ordered by their numbers with pre-releases before the release.
+/// Snapshots are ordered among themselves and before any release, since the
+/// release they lead to is not part of their name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MinecraftVersion(String);
+
+impl MinecraftVersion {
+    /// Whether the version was not known when the manifest was written.
+    pub fn is_unknown(&self) -> bool {
+        self.0 == UNKNOWN_VERSION
+    }
+
+    /// Whether this is a weekly snapshot like `23w13a`.
+    pub fn is_snapshot(&self) -> bool {
+        let b = self.0.as_bytes();
+        b.len() > 5
+            && b[..2].iter().all(u8::is_ascii_digit)
+            && b[2] == b'w'
+            && b[3..5].iter().all(u8::is_ascii_digit)
+    }
+
+    /// The version as a semver pack version, padding `1.20` to `1.20.0`.
+    pub fn to_semver(&self) -> Option<semver::Version> {
+        if self.is_snapshot() {
+            return None;
+        }
+        padded_semver(&self.0)
+    }
+}
+
+/// Parses a version as semver, padding `1.0` to `1.0.0` and `1` to `1.0.0`.
+pub fn padded_semver(version: &str) -> Option<semver::Version> {
+    let mut parts = version.splitn(2, '-');
+    let main = parts.next()?;
+    let numbers = main.split('.').count();
+    let padded = match numbers {
+        1 => format!("{}.0.0", main),
+        2 => format!("{}.0", main),
+        _ => main.to_string(),
+    };
+    let version = match parts.next() {
+        Some(pre) => format!("{}-{}", padded, pre),
+        None => padded,
+    };
+    semver::Version::parse(&version).ok()
+}
+
+impl Default for MinecraftVersion {
+    fn default() -> Self {
+        MinecraftVersion(UNKNOWN_VERSION.to_string())
+    }
+}
+
+impl FromStr for MinecraftVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(anyhow!("Minecraft version is empty"));
+        }
+        Ok(MinecraftVersion(s.to_string()))
+    }
+}
+
+impl Ord for MinecraftVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Old pre-releases are named like `1.14 Pre-Release 1`
+        compare_versions(&self.0.replace(' ', "-"), &other.0.replace(' ', "-"))
+            .then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for MinecraftVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for MinecraftVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for MinecraftVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl JsonSchema for MinecraftVersion {
+    fn schema_name() -> String {
+        "MinecraftVersion".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        version_schema("Minecraft version, like 1.20.1 or 23w13a.", "1.20.1")
+    }
+}
+
+impl<'de> Deserialize<'de> for MinecraftVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_str(VersionVisitor("a Minecraft version"))?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Mod loader version exactly as the loader names it, like `14.23.5.2860`,
+/// `20.4.80-beta` or `0.14.21`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoaderVersion(String);
+
+impl LoaderVersion {
+    /// Whether the version was not known when the manifest was written.
+    pub fn is_unknown(&self) -> bool {
+        self.0 == UNKNOWN_VERSION
+    }
+}
+
+impl Default for LoaderVersion {
+    fn default() -> Self {
+        LoaderVersion(UNKNOWN_VERSION.to_string())
+    }
+}
+
+impl FromStr for LoaderVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(anyhow!("mod loader version is empty"));
+        }
+        if s.contains(char::is_whitespace) {
+            return Err(anyhow!("mod loader version {} contains spaces", s));
+        }
+        Ok(LoaderVersion(s.to_string()))
+    }
+}
+
+impl Ord for LoaderVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_versions(&self.0, &other.0).then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for LoaderVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for LoaderVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for LoaderVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl JsonSchema for LoaderVersion {
+    fn schema_name() -> String {
+        "LoaderVersion".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        version_schema("Mod loader version, like 47.2.0 or 0.14.21.", "47.2.0")
+    }
+}
+
+impl<'de> Deserialize<'de> for LoaderVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_str(VersionVisitor("a mod loader version"))?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+fn version_schema(description: &str, example: &str) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_string()),
+            examples: vec![example.into()],
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+// Takes the text of the value as written, so `1.20` without quotes in YAML
+// stays `1.20` instead of becoming the number `1.2`.
+struct VersionVisitor(&'static str);
+
+impl<'de> de::Visitor<'de> for VersionVisitor {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<String, E> {
+        Ok(v.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mc(v: &str) -> MinecraftVersion {
+        v.parse().unwrap()
+    }
+
+    fn loader(v: &str) -> LoaderVersion {
+        v.parse().unwrap()
+    }
+
+    #[test]
+    fn snapshots_come_before_releases() {
+        assert!(mc("23w13a") < mc("1.0"));
+        assert!(mc("23w13a") < mc("23w14a"));
+        assert!(mc("23w13a").is_snapshot());
+        assert_eq!(mc("23w13a").to_semver(), None);
+    }
+
+    #[test]
+    fn pre_releases_come_before_the_release() {
+        assert!(mc("1.20-pre1") < mc("1.20"));
+        assert!(mc("1.20-pre1") < mc("1.20-rc1"));
+        assert!(mc("1.14 Pre-Release 1") < mc("1.14"));
+        assert!(mc("1.19.4") < mc("1.20-pre1"));
+    }
+
+    #[test]
+    fn missing_parts_are_zero() {
+        assert_eq!(compare_versions("1.20", "1.20.0"), Ordering::Equal);
+        assert_eq!(mc("1.20").to_semver(), mc("1.20.0").to_semver());
+        assert_eq!(padded_semver("1"), Some(semver::Version::new(1, 0, 0)));
+        assert_eq!(padded_semver("1.20"), Some(semver::Version::new(1, 20, 0)));
+        // Ordered next to each other, but still kept as written
+        assert!(mc("1.20") < mc("1.20.0") && mc("1.20.0") < mc("1.20.1"));
+        assert_eq!(mc("1.20").to_string(), "1.20");
+    }
+
+    #[test]
+    fn four_part_forge_versions() {
+        assert!(loader("14.23.5.2859") < loader("14.23.5.2860"));
+        assert!(loader("14.23.5.2860") < loader("14.23.10.0"));
+        assert!(loader("20.4.80-beta") < loader("20.4.80"));
+        assert_eq!(padded_semver("14.23.5.2860"), None);
+    }
+
+    #[test]
+    fn unquoted_yaml_versions_are_not_floats() {
+        let v: MinecraftVersion = serde_yaml::from_str("1.20").unwrap();
+        assert_eq!(v.to_string(), "1.20");
+        let v: LoaderVersion = serde_yaml::from_str("47.10").unwrap();
+        assert_eq!(v.to_string(), "47.10");
+        assert_eq!(serde_yaml::to_string(&v).unwrap().trim(), "---\n\"47.10\"");
+        let v: LoaderVersion = serde_yaml::from_str("14.23.5.2860").unwrap();
+        assert_eq!(v.to_string(), "14.23.5.2860");
+    }
+}
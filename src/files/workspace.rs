@@ -0,0 +1,64 @@
+use crate::files::project::Project;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+pub const WORKSPACE_FILE: &str = ".workspace.yaml";
+
+pub fn get_workspace(dir: &Path) -> Result<Workspace> {
+    let path = dir.join(WORKSPACE_FILE);
+    let mut workspace =
+        Workspace::from_reader(BufReader::new(File::open(&path).with_context(|| {
+            format!("could not open {} for reading", path.to_string_lossy())
+        })?))?;
+    workspace.root = dir.to_path_buf();
+    Ok(workspace)
+}
+
+/// Several packs kept in one repository.
+///
+/// Members are directories relative to the workspace file, each with its own manifest.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    members: Vec<PathBuf>,
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+impl Workspace {
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        serde_yaml::from_reader(reader).with_context(|| "could not deserialize into Workspace")
+    }
+
+    /// Projects for the selected members, or all members if none are selected.
+    ///
+    /// Members are selected by the path listed in the workspace file.
+    pub fn projects(
+        &self,
+        selected: &[PathBuf],
+        manifest: Option<&Path>,
+    ) -> Result<Vec<(PathBuf, Project)>> {
+        for s in selected {
+            if !self.members.contains(s) {
+                return Err(anyhow!(
+                    "{} is not a member of the workspace",
+                    s.to_string_lossy()
+                ));
+            }
+        }
+        let mut projects = Vec::new();
+        for member in &self.members {
+            if !selected.is_empty() && !selected.contains(member) {
+                continue;
+            }
+            let project = Project::new(Some(&self.root.join(member)), manifest)?;
+            projects.push((member.clone(), project));
+        }
+        Ok(projects)
+    }
+}
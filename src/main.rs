@@ -6,12 +6,18 @@ mod commands;
 mod files;
 mod utils;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use commands::{
-    author::AuthorParams, bump::BumpParams, includes::Include, init::InitParams, pack::PackParams,
-    sync::SyncParams,
+    author::AuthorParams, bump::BumpParams, check::CheckParams, convert::ConvertParams,
+    fingerprint::FingerprintParams, import::ImportParams, includes::Include, init::InitParams,
+    migrate::MigrateParams, pack::PackParams, scan::ScanParams, schema::SchemaParams,
+    sync::SyncParams, undo::UndoParams, update::UpdateParams, validate::ValidateParams,
+    verify::VerifyParams,
 };
+use files::{manifest::get_manifest, project::Project, workspace::get_workspace};
+use std::path::PathBuf;
 use structopt::StructOpt;
+use utils::table::print_table;
 
 fn main() -> Result<()> {
     App::run()
@@ -19,6 +25,30 @@ fn main() -> Result<()> {
 
 #[derive(StructOpt, Debug)]
 struct App {
+    /// Run as if mcpacker was started in <dir> instead of the current directory.
+    ///
+    /// Relative paths given to other options and commands are resolved against <dir>.
+    #[structopt(short = "C", long = "dir", parse(from_os_str))]
+    dir: Option<PathBuf>,
+    /// Path to the manifest to use instead of the one in the pack directory.
+    ///
+    /// The format is picked by the extension: .yaml, .toml or .json.
+    #[structopt(long = "manifest", parse(from_os_str))]
+    manifest: Option<PathBuf>,
+    /// Run the command for every pack listed in .workspace.yaml.
+    ///
+    /// Only sync, pack and bump can be ran against a workspace.
+    #[structopt(short = "w", long = "workspace")]
+    workspace: bool,
+    /// Only run against this workspace member. Can be given multiple times.
+    #[structopt(
+        short = "p",
+        long = "pack",
+        parse(from_os_str),
+        number_of_values = 1,
+        requires = "workspace"
+    )]
+    packs: Vec<PathBuf>,
     #[structopt(subcommand)]
     cmd: SubCommand,
 }
@@ -26,7 +56,64 @@ struct App {
 impl App {
     fn run() -> Result<()> {
         let app = App::from_args();
-        app.cmd.run()
+        let project = Project::new(app.dir.as_deref(), app.manifest.as_deref())?;
+        if app.workspace {
+            app.run_workspace(&project)
+        } else {
+            app.cmd.run(&project)
+        }
+    }
+
+    fn run_workspace(&self, project: &Project) -> Result<()> {
+        match &self.cmd {
+            SubCommand::Sync(_) | SubCommand::Pack(_) => {}
+            SubCommand::Bump(p) if !p.git_tag() => {}
+            SubCommand::Bump(_) => {
+                return Err(anyhow!("--git-tag can not be used with --workspace"));
+            }
+            _ => {
+                return Err(anyhow!(
+                    "only sync, pack and bump can be used with --workspace"
+                ))
+            }
+        }
+        let workspace = get_workspace(project.root())?;
+        let mut results = Vec::new();
+        for (member, project) in workspace.projects(&self.packs, self.manifest.as_deref())? {
+            println!("==> {}", member.to_string_lossy());
+            let res = self.cmd.run(&project);
+            if let Err(e) = &res {
+                println!("{:#}", e);
+            }
+            results.push((member, project, res));
+        }
+        let mut failed = 0;
+        let mut rows = vec![vec![
+            "PACK".to_string(),
+            "NAME".to_string(),
+            "VERSION".to_string(),
+            "RESULT".to_string(),
+        ]];
+        for (member, project, res) in &results {
+            let (name, version) = match get_manifest(project) {
+                Ok(m) => (m.name, m.version.to_string()),
+                Err(_) => ("-".to_string(), "-".to_string()),
+            };
+            let status = match res {
+                Ok(_) => "ok".to_string(),
+                Err(e) => {
+                    failed += 1;
+                    format!("error: {}", e)
+                }
+            };
+            rows.push(vec![member.to_string_lossy().into(), name, version, status]);
+        }
+        println!();
+        print_table(&rows);
+        if failed > 0 {
+            return Err(anyhow!("{} of {} packs failed", failed, results.len()));
+        }
+        Ok(())
     }
 }
 
@@ -37,11 +124,38 @@ enum SubCommand {
     /// Will use minecraftinstance.json if it exists.
     /// If it doesn't exists, it will prompt for some information to be able to start a new modpack.
     Init(InitParams),
+    /// Start a new mcpacker project from a CurseForge modpack zip.
+    ///
+    /// Mods are added to the manifest and the overrides are extracted to the pack
+    /// directory and added to the includes.
+    Import(ImportParams),
     /// Syncronize the manifest with the minecraftinstance.json.
     ///
     /// Downloads mods that are missing and adds jars to override if not in project list.
     /// This can be assumed as twitch app will remove jar files if mod is uninstalled.
     Sync(SyncParams),
+    /// Resolve the mods of an mcpacker.yaml manifest to files in mcpacker.lock.
+    ///
+    /// Mods missing from the lock file get the newest file for the Minecraft version
+    /// and mod loader of the pack. Mods that are no longer in the manifest are dropped.
+    Update(UpdateParams),
+    /// Check the jars in the mods folder without changing anything.
+    ///
+    /// Reports tracked mods that are missing or do not match the manifest and
+    /// mod IDs declared by more than one jar.
+    Verify(VerifyParams),
+    /// Check that the dependencies of every mod are installed, without going online.
+    ///
+    /// Uses the dependencies declared in the jars, including the Minecraft and mod loader versions.
+    Check(CheckParams),
+    /// Identify jars in the mods folder that are not in the manifest.
+    ///
+    /// Jars are looked up on CurseForge by their fingerprint and can be added as mods.
+    Scan(ScanParams),
+    /// Print the CurseForge fingerprint, size and hashes of files.
+    ///
+    /// Useful to find out why a jar does not match the fingerprint in the manifest.
+    Fingerprint(FingerprintParams),
     /// Create the modpack as a zip file.
     Pack(PackParams),
     /// Modify the includes section of the manifest.
@@ -53,17 +167,52 @@ enum SubCommand {
     /// This only updates the manifest that only takes effect after a pack.
     /// Will need to import the new pack to see author changes.
     Author(AuthorParams),
+    /// Restore the manifest from before the last change.
+    ///
+    /// Every change to the manifest keeps the previous version as .manifest.yaml.bak.
+    /// Running undo again will redo the change.
+    Undo(UndoParams),
+    /// Upgrade the manifest to the schema version of this version of mcpacker.
+    ///
+    /// Older manifests are also upgraded in memory whenever they are read,
+    /// and on disk the next time a command changes them.
+    Migrate(MigrateParams),
+    /// Check the manifest for mistakes without changing anything.
+    ///
+    /// Reports YAML errors, mods sharing a project ID or file name, includes outside of
+    /// the pack directory and files that do not exist, with the line and column of each.
+    Validate(ValidateParams),
+    /// Print the JSON Schema of the manifest.
+    ///
+    /// YAML language servers can use it for completion and validation of .manifest.yaml.
+    Schema(SchemaParams),
+    /// Convert the manifest to another format.
+    ///
+    /// The old manifest is kept with .bak added to its name. This can not be undone with undo,
+    /// rename the old manifest back and remove the new one instead.
+    Convert(ConvertParams),
 }
 
 impl SubCommand {
-    fn run(&self) -> Result<()> {
+    fn run(&self, project: &Project) -> Result<()> {
         match &self {
-            SubCommand::Init(p) => p.run(),
-            SubCommand::Sync(p) => p.run(),
-            SubCommand::Pack(p) => p.run(),
-            SubCommand::Include(p) => p.run(),
-            SubCommand::Bump(p) => p.run(),
-            SubCommand::Author(p) => p.run(),
+            SubCommand::Init(p) => p.run(project),
+            SubCommand::Import(p) => p.run(project),
+            SubCommand::Sync(p) => p.run(project),
+            SubCommand::Update(p) => p.run(project),
+            SubCommand::Verify(p) => p.run(project),
+            SubCommand::Check(p) => p.run(project),
+            SubCommand::Scan(p) => p.run(project),
+            SubCommand::Fingerprint(p) => p.run(project),
+            SubCommand::Pack(p) => p.run(project),
+            SubCommand::Include(p) => p.run(project),
+            SubCommand::Bump(p) => p.run(project),
+            SubCommand::Author(p) => p.run(project),
+            SubCommand::Undo(p) => p.run(project),
+            SubCommand::Migrate(p) => p.run(project),
+            SubCommand::Validate(p) => p.run(project),
+            SubCommand::Schema(p) => p.run(project),
+            SubCommand::Convert(p) => p.run(project),
         }
     }
 }
@@ -1,3 +1,9 @@
 pub mod compare;
 pub mod murmur2;
+pub mod pack_ignore;
+pub mod patterns;
+pub mod table;
+#[cfg(test)]
+pub mod test_dir;
 pub mod twitch_api;
+pub mod version_range;
@@ -28,6 +28,18 @@ pub fn murmurhash2_32(key: &[u8], seed: u32) -> u32 {
     h ^ (h >> 15)
 }
 
+/// Fingerprint CurseForge gives a file.
+///
+/// It is the hash of the contents with all whitespace bytes removed.
+pub fn fingerprint(buf: &[u8]) -> u32 {
+    let buf: Vec<u8> = buf
+        .iter()
+        .copied()
+        .filter(|b| !matches!(b, 9 | 10 | 13 | 32))
+        .collect();
+    murmurhash2_32(&buf, 1)
+}
+
 // returns value of h
 fn mix(mut h: u32, mut k: u32) -> u32 {
     k = k.wrapping_mul(M);
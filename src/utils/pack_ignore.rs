@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use git2::Repository;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+
+pub const MCPACKER_IGNORE_FILE: &str = ".mcpackerignore";
+
+/// Files inside included directories that should not be packed.
+///
+/// Reads `.mcpackerignore` from the pack directory and, when asked to,
+/// the ignore rules of the git repository the pack is in.
+pub struct PackIgnore {
+    mcpacker: Gitignore,
+    git: Option<(Repository, PathBuf)>,
+}
+
+impl PackIgnore {
+    pub fn new(dir: &Path, gitignore: bool) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new(dir);
+        let ignore_file = dir.join(MCPACKER_IGNORE_FILE);
+        if ignore_file.is_file() {
+            if let Some(e) = builder.add(&ignore_file) {
+                return Err(anyhow::Error::new(e)
+                    .context(format!("could not read {}", ignore_file.to_string_lossy())));
+            }
+        }
+        let mcpacker = builder.build().with_context(|| {
+            format!(
+                "could not build rules from {}",
+                ignore_file.to_string_lossy()
+            )
+        })?;
+        let git = if gitignore {
+            let repo = Repository::discover(dir).with_context(|| {
+                format!(
+                    "could not find a git repository for {}",
+                    dir.to_string_lossy()
+                )
+            })?;
+            let workdir = repo
+                .workdir()
+                .with_context(|| "repository does not have a working directory")?
+                .canonicalize()
+                .with_context(|| "could not normalize repository working directory")?;
+            Some((repo, workdir))
+        } else {
+            None
+        };
+        Ok(PackIgnore { mcpacker, git })
+    }
+
+    /// Whether the file at `abs`, which is `rel` inside the pack directory, is ignored.
+    pub fn is_ignored(&self, abs: &Path, rel: &Path, is_dir: bool) -> Result<bool> {
+        if self
+            .mcpacker
+            .matched_path_or_any_parents(rel, is_dir)
+            .is_ignore()
+        {
+            return Ok(true);
+        }
+        if let Some((repo, workdir)) = &self.git {
+            let abs = abs
+                .canonicalize()
+                .with_context(|| format!("could not normalize {}", abs.to_string_lossy()))?;
+            if let Ok(in_repo) = abs.strip_prefix(workdir) {
+                return repo.is_path_ignored(in_repo).with_context(|| {
+                    format!("could not check if {} is ignored", abs.to_string_lossy())
+                });
+            }
+        }
+        Ok(false)
+    }
+}
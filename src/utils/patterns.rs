@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Component, Path, PathBuf};
+
+const GLOB_CHARS: &[char] = &['*', '?', '[', '{'];
+
+/// Whether `s` should be treated as a glob pattern instead of a literal path.
+pub fn is_pattern(s: &str) -> bool {
+    s.contains(GLOB_CHARS)
+}
+
+/// Gitignore style patterns relative to a directory.
+///
+/// Patterns are anchored to the directory unless they start with `**/`,
+/// so `config` only matches the top level config folder.
+#[derive(Debug)]
+pub struct Patterns {
+    matcher: Gitignore,
+    // (anchored, as given) for reporting which pattern matched
+    originals: Vec<(String, String)>,
+    prefixes: Vec<PathBuf>,
+}
+
+impl Patterns {
+    pub fn new<I, S>(root: &Path, patterns: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut builder = GitignoreBuilder::new(root);
+        let mut originals = Vec::new();
+        let mut prefixes = Vec::new();
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            let anchored = if pattern.starts_with('/') || pattern.starts_with("**") {
+                pattern.to_string()
+            } else {
+                format!("/{}", pattern)
+            };
+            let _ = builder
+                .add_line(None, &anchored)
+                .with_context(|| format!("{} is not a valid pattern", pattern))?;
+            prefixes.push(literal_prefix(pattern));
+            originals.push((anchored, pattern.to_string()));
+        }
+        let matcher = builder
+            .build()
+            .with_context(|| "could not build patterns")?;
+        Ok(Patterns {
+            matcher,
+            originals,
+            prefixes,
+        })
+    }
+
+    /// The pattern that matches `p` or one of its parents.
+    ///
+    /// `p` must be relative to the directory the patterns were created with,
+    /// a leading `/` is allowed like it is for patterns.
+    pub fn matched(&self, p: &Path, is_dir: bool) -> Option<&str> {
+        let p = p.strip_prefix("/").unwrap_or(p);
+        let glob = self
+            .matcher
+            .matched_path_or_any_parents(p, is_dir)
+            .inner()?
+            .original();
+        self.originals
+            .iter()
+            .find(|(anchored, _)| anchored == glob)
+            .map(|(_, original)| original.as_str())
+    }
+
+    /// The patterns as they were given.
+    pub fn originals(&self) -> impl Iterator<Item = &str> {
+        self.originals.iter().map(|(_, original)| original.as_str())
+    }
+
+    /// Directories that contain every file the patterns can match.
+    ///
+    /// Walking these instead of the whole directory keeps large folders,
+    /// like saves, from being read when they are not included.
+    pub fn prefixes(&self) -> Vec<&Path> {
+        let mut prefixes: Vec<&Path> = Vec::new();
+        let mut sorted: Vec<&PathBuf> = self.prefixes.iter().collect();
+        sorted.sort();
+        for p in sorted {
+            if !prefixes.iter().any(|existing| p.starts_with(existing)) {
+                prefixes.push(p);
+            }
+        }
+        prefixes
+    }
+}
+
+fn literal_prefix(pattern: &str) -> PathBuf {
+    let mut prefix = PathBuf::new();
+    for c in Path::new(pattern.trim_start_matches('/')).components() {
+        if let Component::Normal(s) = c {
+            if is_pattern(&s.to_string_lossy()) {
+                break;
+            }
+            prefix.push(s);
+        }
+    }
+    prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matched_anchored_path() {
+        let patterns = Patterns::new(Path::new("pack"), ["options.txt"]).unwrap();
+        assert_eq!(patterns.matched(Path::new("/config/**"), false), None);
+        assert_eq!(
+            patterns.matched(Path::new("/options.txt"), false),
+            Some("options.txt")
+        );
+    }
+
+    #[test]
+    fn matched_anchored_pattern() {
+        let patterns = Patterns::new(Path::new("pack"), ["/config/**"]).unwrap();
+        assert_eq!(
+            patterns.matched(Path::new("config/a.cfg"), false),
+            Some("/config/**")
+        );
+        assert_eq!(
+            patterns.matched(Path::new("/config/a.cfg"), false),
+            Some("/config/**")
+        );
+        assert_eq!(
+            patterns.matched(Path::new("other/config/a.cfg"), false),
+            None
+        );
+    }
+}
@@ -0,0 +1,18 @@
+/// Prints rows as columns padded to the widest cell, the first row being the header.
+pub fn print_table(rows: &[Vec<String>]) {
+    let mut widths = vec![0; rows.first().map_or(0, |r| r.len())];
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, w)| format!("{:w$}", cell, w = w))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
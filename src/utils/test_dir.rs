@@ -0,0 +1,36 @@
+use std::{
+    fs::{create_dir_all, remove_dir_all, write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Scratch directory for tests that need files on disk, removed when dropped.
+#[derive(Debug)]
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("mcpacker-{}-{}", name, process::id()));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        TestDir(dir.canonicalize().unwrap())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Writes `contents` to `rel`, creating the directories it is in.
+    pub fn write(&self, rel: &str, contents: &str) -> PathBuf {
+        let path = self.0.join(rel);
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.0);
+    }
+}
@@ -1,11 +1,16 @@
+use crate::files::minecraft_instance::InstalledFile;
 use anyhow::{anyhow, Context, Result};
 use reqwest::Client;
+use serde::Deserialize;
 use tokio::{
     io::{self, AsyncWriteExt},
     stream::StreamExt,
 };
 use url::Url;
 
+const MINECRAFT_GAME_ID: &str = "432";
+const MODS_SECTION_ID: &str = "6";
+
 #[derive(Clone)]
 pub struct TwitchAPI {
     client: Client,
@@ -18,6 +23,142 @@ impl TwitchAPI {
         }
     }
 
+    /// Information about a file of a project, like its name, size and fingerprint.
+    pub async fn file(&self, project: u32, file: u32) -> Result<InstalledFile> {
+        let url = Url::parse(
+            format!(
+                "https://addons-ecs.forgesvc.net/api/v2/addon/{}/file/{}",
+                project, file
+            )
+            .as_str(),
+        )
+        .expect("could not create get file url");
+        let resp = self.client.get(url.clone()).send().await.with_context(|| {
+            format!(
+                "could not send request to {} for project {} and file {}",
+                url, project, file
+            )
+        })?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(format!(
+                "could not get file for project {} and file {}: status code {}",
+                project,
+                file,
+                resp.status()
+            )));
+        }
+        resp.json().await.with_context(|| {
+            format!(
+                "could not deserialize file for project {} and file {}",
+                project, file
+            )
+        })
+    }
+
+    /// Information about a project, like its name and authors.
+    pub async fn addon(&self, project: u32) -> Result<Addon> {
+        let url = Url::parse(
+            format!("https://addons-ecs.forgesvc.net/api/v2/addon/{}", project).as_str(),
+        )
+        .expect("could not create get addon url");
+        let resp = self.client.get(url.clone()).send().await.with_context(|| {
+            format!("could not send request to {} for project {}", url, project)
+        })?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(format!(
+                "could not get project {}: status code {}",
+                project,
+                resp.status()
+            )));
+        }
+        resp.json()
+            .await
+            .with_context(|| format!("could not deserialize project {}", project))
+    }
+
+    /// The Minecraft mod with exactly this slug, the last part of its CurseForge url.
+    pub async fn addon_by_slug(&self, slug: &str) -> Result<Addon> {
+        let mut url = Url::parse("https://addons-ecs.forgesvc.net/api/v2/addon/search")
+            .expect("could not create search url");
+        let _ = url
+            .query_pairs_mut()
+            .append_pair("gameId", MINECRAFT_GAME_ID)
+            .append_pair("sectionId", MODS_SECTION_ID)
+            .append_pair("searchFilter", slug);
+        let resp = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .with_context(|| format!("could not send request to {} for {}", url, slug))?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(format!(
+                "could not search for {}: status code {}",
+                slug,
+                resp.status()
+            )));
+        }
+        let addons: Vec<Addon> = resp
+            .json()
+            .await
+            .with_context(|| format!("could not deserialize search results for {}", slug))?;
+        addons
+            .into_iter()
+            .find(|a| a.slug == slug)
+            .ok_or_else(|| anyhow!("there is no mod with the slug {}", slug))
+    }
+
+    /// Every file of a project, for any Minecraft version.
+    pub async fn files(&self, project: u32) -> Result<Vec<ProjectFile>> {
+        let url = Url::parse(
+            format!(
+                "https://addons-ecs.forgesvc.net/api/v2/addon/{}/files",
+                project
+            )
+            .as_str(),
+        )
+        .expect("could not create get files url");
+        let resp = self.client.get(url.clone()).send().await.with_context(|| {
+            format!("could not send request to {} for project {}", url, project)
+        })?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(format!(
+                "could not get files for project {}: status code {}",
+                project,
+                resp.status()
+            )));
+        }
+        resp.json()
+            .await
+            .with_context(|| format!("could not deserialize files for project {}", project))
+    }
+
+    /// Files that exactly match the given fingerprints.
+    ///
+    /// Fingerprints without a match are left out.
+    pub async fn fingerprints(&self, fingerprints: &[u32]) -> Result<Vec<FingerprintMatch>> {
+        let url = Url::parse("https://addons-ecs.forgesvc.net/api/v2/fingerprint")
+            .expect("could not create fingerprint url");
+        let resp = self
+            .client
+            .post(url.clone())
+            .json(fingerprints)
+            .send()
+            .await
+            .with_context(|| format!("could not send request to {}", url))?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(format!(
+                "could not match fingerprints: status code {}",
+                resp.status()
+            )));
+        }
+        let matches: FingerprintMatches = resp
+            .json()
+            .await
+            .with_context(|| "could not deserialize fingerprint matches")?;
+        Ok(matches.exact_matches)
+    }
+
     async fn download_url(&self, project: u32, file: u32) -> Result<Url> {
         let url = Url::parse(
             format!(
@@ -100,3 +241,44 @@ impl TwitchAPI {
         Ok(())
     }
 }
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct FingerprintMatches {
+    exact_matches: Vec<FingerprintMatch>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Addon {
+    pub id: u32,
+    pub name: String,
+    pub slug: String,
+    pub authors: Vec<AddonAuthor>,
+    pub website_url: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AddonAuthor {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectFile {
+    #[serde(flatten)]
+    pub file: InstalledFile,
+    /// Minecraft versions and mod loaders the file is for, like `1.20.1` and `Forge`.
+    pub game_version: Vec<String>,
+    /// When the file was uploaded, as an ISO 8601 timestamp.
+    pub file_date: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintMatch {
+    /// Project the file belongs to.
+    pub id: u32,
+    pub file: InstalledFile,
+}
@@ -0,0 +1,242 @@
+use std::cmp::Ordering;
+
+/// Whether `version` is in `range`, or None if the range can not be understood.
+///
+/// Understands the Maven ranges Forge uses, like `[43,)` or `[1.19,1.20),[1.21]`,
+/// and the predicates Fabric and Quilt use, like `>=0.14 <0.15`, `1.19.x` or `~1.2`
+/// with `||` between alternatives.
+pub fn version_matches(range: &str, version: &str) -> Option<bool> {
+    let range = range.trim();
+    if range.is_empty() || range == "*" {
+        Some(true)
+    } else if range.starts_with('[') || range.starts_with('(') {
+        maven_matches(range, version)
+    } else {
+        Some(
+            range
+                .split("||")
+                .map(|alt| {
+                    alt.split_whitespace()
+                        .map(|p| predicate_matches(p, version))
+                        .collect::<Option<Vec<bool>>>()
+                        .map(|all| all.into_iter().all(|m| m))
+                })
+                .collect::<Option<Vec<bool>>>()?
+                .into_iter()
+                .any(|m| m),
+        )
+    }
+}
+
+/// Compares versions that are not necessarily semver, like `1.20` or `11.6.0.1015`.
+///
+/// Numbers are compared as numbers, missing parts count as 0 and pre-releases
+/// after `-` come before the release.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a_main, a_pre) = split_pre_release(a);
+    let (b_main, b_pre) = split_pre_release(b);
+    match compare_parts(a_main, b_main) {
+        Ordering::Equal => match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare_parts(a, b),
+        },
+        o => o,
+    }
+}
+
+fn split_pre_release(v: &str) -> (&str, Option<&str>) {
+    let v = v.trim().split('+').next().unwrap_or_default();
+    let mut parts = v.splitn(2, '-');
+    (parts.next().unwrap_or_default(), parts.next())
+}
+
+fn compare_parts(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        let (x, y) = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (x, y) => (x.unwrap_or("0"), y.unwrap_or("0")),
+        };
+        let o = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Greater,
+            (Err(_), Ok(_)) => Ordering::Less,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if o != Ordering::Equal {
+            return o;
+        }
+    }
+}
+
+// Maven ranges are a comma separated list of restrictions where any of them will do.
+fn maven_matches(range: &str, version: &str) -> Option<bool> {
+    let mut rest = range;
+    let mut matched = false;
+    while !rest.is_empty() {
+        let open = rest.chars().next()?;
+        let close_idx = rest.find(&[']', ')'][..])?;
+        let close = rest[close_idx..].chars().next()?;
+        if restriction_matches(open, &rest[1..close_idx], close, version)? {
+            matched = true;
+        }
+        rest = rest[close_idx + 1..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    Some(matched)
+}
+
+fn restriction_matches(open: char, inner: &str, close: char, version: &str) -> Option<bool> {
+    if !inner.contains(',') {
+        // [1.0] is the only version allowed
+        return match (open, close) {
+            ('[', ']') => Some(compare_versions(version, inner) == Ordering::Equal),
+            _ => None,
+        };
+    }
+    let mut bounds = inner.splitn(2, ',');
+    let lower = bounds.next()?.trim();
+    let upper = bounds.next()?.trim();
+    if !lower.is_empty() {
+        match compare_versions(version, lower) {
+            Ordering::Less => return Some(false),
+            Ordering::Equal if open == '(' => return Some(false),
+            _ => {}
+        }
+    }
+    if !upper.is_empty() {
+        match compare_versions(version, upper) {
+            Ordering::Greater => return Some(false),
+            Ordering::Equal if close == ')' => return Some(false),
+            _ => {}
+        }
+    }
+    Some(true)
+}
+
+fn predicate_matches(predicate: &str, version: &str) -> Option<bool> {
+    if predicate == "*" {
+        return Some(true);
+    }
+    let op = [">=", "<=", ">", "<", "=", "^", "~"]
+        .iter()
+        .find(|op| predicate.starts_with(*op))
+        .copied()
+        .unwrap_or("");
+    let target = predicate[op.len()..].trim();
+    if target.is_empty() {
+        return None;
+    }
+    let parts: Vec<&str> = target.split('.').collect();
+    // 1.19.x only fixes the parts before the wildcard
+    if let Some(i) = parts.iter().position(|p| matches!(*p, "x" | "X" | "*")) {
+        let prefix = &parts[..i];
+        return match op {
+            "" | "=" => {
+                let (main, _) = split_pre_release(version);
+                Some(
+                    main.split('.')
+                        .chain(std::iter::repeat("0"))
+                        .zip(prefix.iter())
+                        .all(|(v, p)| compare_parts(v, p) == Ordering::Equal),
+                )
+            }
+            _ => predicate_matches(&format!("{}{}", op, prefix.join(".")), version),
+        };
+    }
+    let o = compare_versions(version, target);
+    Some(match op {
+        ">=" => o != Ordering::Less,
+        "<=" => o != Ordering::Greater,
+        ">" => o == Ordering::Greater,
+        "<" => o == Ordering::Less,
+        "^" | "~" => {
+            let major = parts[0].parse::<u64>().ok()?;
+            // ^ keeps the major version and ~ keeps the minor version
+            let upper = match (op, parts.get(1)) {
+                ("~", Some(minor)) => format!("{}.{}", major, minor.parse::<u64>().ok()? + 1),
+                _ => format!("{}", major + 1),
+            };
+            o != Ordering::Less && compare_versions(version, &upper) == Ordering::Less
+        }
+        _ => o == Ordering::Equal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maven_inclusive_and_exclusive_bounds() {
+        assert_eq!(version_matches("[1.19,1.20)", "1.19"), Some(true));
+        assert_eq!(version_matches("[1.19,1.20)", "1.19.4"), Some(true));
+        assert_eq!(version_matches("[1.19,1.20)", "1.20"), Some(false));
+        assert_eq!(version_matches("(1.19,1.20]", "1.19"), Some(false));
+        assert_eq!(version_matches("(1.19,1.20]", "1.20"), Some(true));
+        assert_eq!(version_matches("(1.19,1.20]", "1.20.1"), Some(false));
+    }
+
+    #[test]
+    fn maven_exact_version() {
+        assert_eq!(version_matches("[1.0]", "1.0"), Some(true));
+        assert_eq!(version_matches("[1.0]", "1.0.0"), Some(true));
+        assert_eq!(version_matches("[1.0]", "1.0.1"), Some(false));
+        assert_eq!(version_matches("(1.0)", "1.0"), None);
+    }
+
+    #[test]
+    fn maven_open_upper_bound() {
+        assert_eq!(version_matches("[47,)", "47.2.0"), Some(true));
+        assert_eq!(version_matches("[47,)", "100"), Some(true));
+        assert_eq!(version_matches("[47,)", "46.0.14"), Some(false));
+    }
+
+    #[test]
+    fn maven_alternatives() {
+        assert_eq!(version_matches("[1.19,1.20),[1.21]", "1.21"), Some(true));
+        assert_eq!(version_matches("[1.19,1.20),[1.21]", "1.20.1"), Some(false));
+    }
+
+    #[test]
+    fn any_version() {
+        assert_eq!(version_matches("*", "1.20.1"), Some(true));
+        assert_eq!(version_matches("", "23w13a"), Some(true));
+    }
+
+    #[test]
+    fn fabric_predicates() {
+        assert_eq!(version_matches(">=0.14 <0.16", "0.14.21"), Some(true));
+        assert_eq!(version_matches(">=0.14 <0.16", "0.15"), Some(true));
+        assert_eq!(version_matches(">=0.14 <0.16", "0.16.0"), Some(false));
+        assert_eq!(version_matches(">=0.14 <0.16", "0.13.3"), Some(false));
+        assert_eq!(version_matches("<1.19 || >=1.20", "1.19.2"), Some(false));
+        assert_eq!(version_matches("<1.19 || >=1.20", "1.20.1"), Some(true));
+        assert_eq!(version_matches("1.19.x", "1.19.4"), Some(true));
+        assert_eq!(version_matches("1.19.x", "1.20"), Some(false));
+        assert_eq!(version_matches(">=", "1.20"), None);
+    }
+
+    #[test]
+    fn fabric_tilde_and_caret() {
+        assert_eq!(version_matches("~1.2", "1.2"), Some(true));
+        assert_eq!(version_matches("~1.2", "1.2.9"), Some(true));
+        assert_eq!(version_matches("~1.2", "1.3"), Some(false));
+        assert_eq!(version_matches("~1.2", "1.1.9"), Some(false));
+        assert_eq!(version_matches("^1.2", "1.9"), Some(true));
+        assert_eq!(version_matches("^1.2", "2.0"), Some(false));
+    }
+
+    #[test]
+    fn pre_releases_and_snapshots() {
+        assert_eq!(version_matches("[1.20,)", "1.20-pre1"), Some(false));
+        assert_eq!(version_matches(">=1.19", "1.20-rc1"), Some(true));
+        assert_eq!(version_matches(">=1.19", "23w13a"), Some(false));
+        assert_eq!(version_matches("23w13a", "23w13a"), Some(true));
+        assert_eq!(compare_versions("1.20-pre1", "1.20-rc1"), Ordering::Less);
+        assert_eq!(compare_versions("1.20+build.5", "1.20"), Ordering::Equal);
+    }
+}